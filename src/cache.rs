@@ -6,7 +6,7 @@ use std::{
 };
 
 use crate::{
-    color::color::Source,
+    color::color::{ExtractionOptions, Source},
     scheme::{SchemeTypes, Schemes},
     util::config::{get_proj_path, ProjectDirsTypes},
 };
@@ -138,6 +138,7 @@ pub struct ImageCache {
     contrast: Option<f64>,
     lightness_dark: Option<f64>,
     lightness_light: Option<f64>,
+    extraction: String,
     source: Option<PathBuf>,
     cache_folder: PathBuf,
 }
@@ -149,9 +150,10 @@ impl ImageCache {
         contrast: Option<f64>,
         lightness_dark: Option<f64>,
         lightness_light: Option<f64>,
+        extraction: &ExtractionOptions,
     ) -> Self {
         let pathbuf = match source {
-            Source::Image { path } => Some(PathBuf::from(path)),
+            Source::Image { paths, .. } => paths.first().map(PathBuf::from),
             _ => None,
         };
 
@@ -165,6 +167,7 @@ impl ImageCache {
             contrast,
             lightness_dark,
             lightness_light,
+            extraction: extraction.cache_key(),
             source: pathbuf,
             cache_folder,
         }
@@ -223,7 +226,7 @@ impl ImageCache {
 
     fn get_name(&self) -> PathBuf {
         let name = format!(
-            "{}.{}.{:?}.{:?}.{:?}.{:?}.json",
+            "{}.{}.{:?}.{:?}.{:?}.{:?}.{}.json",
             self.source
                 .as_ref()
                 .unwrap()
@@ -234,7 +237,8 @@ impl ImageCache {
             &self.stype,
             &self.contrast,
             &self.lightness_dark,
-            &self.lightness_light
+            &self.lightness_light,
+            &self.extraction
         );
 
         PathBuf::from(name)
@@ -251,15 +255,29 @@ impl ImageCache {
 }
 
 fn get_cache(source: &Source) -> Option<String> {
-    match &source {
-        Source::Image { path } => match hash_image_from_path(path) {
-            Ok(v) => Some(v),
-            Err(e) => {
-                eprintln!("{}", e);
-                None
+    match hash_images(source) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{}", e);
+            None
+        }
+    }
+}
+
+/// The hash of a single image, or of the hashes and weights of several images.
+fn hash_images(source: &Source) -> Result<Option<String>, Report> {
+    let images = source.images()?;
+
+    match images[..] {
+        [] => Ok(None),
+        [(path, _)] => hash_image_from_path(path).map(Some),
+        _ => {
+            let mut hasher = Sha256::new();
+            for (path, weight) in images {
+                hasher.update(format!("{}:{:?}\n", hash_image_from_path(path)?, weight));
             }
-        },
-        _ => None,
+            Ok(Some(format!("{:x}", hasher.finalize())))
+        }
     }
 }
 
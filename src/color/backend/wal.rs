@@ -1,6 +1,6 @@
-use crate::color::base16::PaletteBackend;
+use crate::color::{base16::PaletteBackend, region::WeightedImage};
 use colorsys::Rgb;
-use image::{imageops::FilterType, RgbImage};
+use image::{imageops::FilterType, GenericImageView};
 use palette::IntoColor;
 use palette::Oklab;
 
@@ -23,9 +23,13 @@ impl Default for WalBackend {
 }
 
 impl PaletteBackend for WalBackend {
-    fn extract(&self, image: &RgbImage) -> Vec<Rgb> {
-        let resized = resize_image(image, self.resize_width);
-        let samples = sample_pixels(&resized, self.sample_step);
+    fn extract(&self, frames: &[WeightedImage]) -> Vec<Rgb> {
+        let samples: Vec<([f32; 3], f32)> = frames
+            .iter()
+            .flat_map(|frame| {
+                sample_pixels(&resize_image(frame, self.resize_width), self.sample_step)
+            })
+            .collect();
 
         if samples.is_empty() {
             return Vec::new();
@@ -48,8 +52,8 @@ impl PaletteBackend for WalBackend {
     }
 }
 
-fn resize_image(image: &RgbImage, target_width: u32) -> RgbImage {
-    let (w, h) = image.dimensions();
+fn resize_image(image: &WeightedImage, target_width: u32) -> WeightedImage {
+    let (w, h) = image.image.dimensions();
 
     if w <= target_width {
         return image.clone();
@@ -58,29 +62,36 @@ fn resize_image(image: &RgbImage, target_width: u32) -> RgbImage {
     let scale = target_width as f32 / w as f32;
     let target_height = (h as f32 * scale).round() as u32;
 
-    image::imageops::resize(image, target_width, target_height, FilterType::Triangle)
+    image.resize_exact(target_width, target_height, FilterType::Triangle)
 }
 
-fn sample_pixels(image: &RgbImage, step: u32) -> Vec<[f32; 3]> {
+/// Every `step`th pixel with its weight, leaving out pixels that don't count at all.
+fn sample_pixels(image: &WeightedImage, step: u32) -> Vec<([f32; 3], f32)> {
+    let rgb = image.image.to_rgb8();
     let mut out = Vec::new();
 
-    for y in (0..image.height()).step_by(step as usize) {
-        for x in (0..image.width()).step_by(step as usize) {
-            let p = image.get_pixel(x, y);
-            out.push([p[0] as f32, p[1] as f32, p[2] as f32]);
+    for y in (0..rgb.height()).step_by(step as usize) {
+        for x in (0..rgb.width()).step_by(step as usize) {
+            let weight = image.weight(x, y) as f32;
+            if weight <= 0.0 {
+                continue;
+            }
+
+            let p = rgb.get_pixel(x, y);
+            out.push(([p[0] as f32, p[1] as f32, p[2] as f32], weight));
         }
     }
 
     out
 }
 
-fn kmeans(samples: &[[f32; 3]], k: usize, iterations: usize) -> Vec<[f32; 3]> {
-    let mut centroids: Vec<[f32; 3]> = samples.iter().take(k).cloned().collect();
+fn kmeans(samples: &[([f32; 3], f32)], k: usize, iterations: usize) -> Vec<[f32; 3]> {
+    let mut centroids: Vec<[f32; 3]> = samples.iter().take(k).map(|(p, _)| *p).collect();
 
     for _ in 0..iterations {
-        let mut buckets: Vec<Vec<[f32; 3]>> = vec![Vec::new(); k];
+        let mut buckets: Vec<Vec<([f32; 3], f32)>> = vec![Vec::new(); k];
 
-        for sample in samples {
+        for (sample, weight) in samples {
             let mut best = 0;
             let mut best_dist = f32::MAX;
 
@@ -95,7 +106,7 @@ fn kmeans(samples: &[[f32; 3]], k: usize, iterations: usize) -> Vec<[f32; 3]> {
                 }
             }
 
-            buckets[best].push(*sample);
+            buckets[best].push((*sample, *weight));
         }
 
         for (i, bucket) in buckets.iter().enumerate() {
@@ -104,17 +115,15 @@ fn kmeans(samples: &[[f32; 3]], k: usize, iterations: usize) -> Vec<[f32; 3]> {
             }
 
             let mut sum = [0.0; 3];
-            for p in bucket {
-                sum[0] += p[0];
-                sum[1] += p[1];
-                sum[2] += p[2];
+            let mut total = 0.0;
+            for (p, weight) in bucket {
+                sum[0] += p[0] * weight;
+                sum[1] += p[1] * weight;
+                sum[2] += p[2] * weight;
+                total += weight;
             }
 
-            centroids[i] = [
-                sum[0] / bucket.len() as f32,
-                sum[1] / bucket.len() as f32,
-                sum[2] / bucket.len() as f32,
-            ];
+            centroids[i] = [sum[0] / total, sum[1] / total, sum[2] / total];
         }
     }
 
@@ -4,12 +4,12 @@ use crate::{
         color::{get_source_color_from_color, ColorFormat, Source},
         format::{argb_from_rgb, rgb_from_argb},
         math::{luminance, saturation},
+        region::{Region, WeightedImage},
     },
     scheme::Schemes,
 };
 use color_eyre::{eyre::WrapErr, Report};
 use colorsys::{Hsl, Rgb};
-use image::{ImageReader, RgbImage};
 use indexmap::IndexMap;
 use material_colors::{
     color::Argb,
@@ -39,7 +39,8 @@ impl Backend {
 }
 
 pub trait PaletteBackend {
-    fn extract(&self, image: &RgbImage) -> Vec<Rgb>;
+    /// Extracts a palette from every frame of an image together.
+    fn extract(&self, frames: &[WeightedImage]) -> Vec<Rgb>;
 }
 
 fn drag_hue(source_hue: f64, target_hue: f64, amount: f64) -> f64 {
@@ -150,17 +151,18 @@ fn interpolate_grays(base00: &Rgb, base05: &Rgb, dark: bool) -> Vec<Argb> {
     grays
 }
 
-pub fn generate_base16_schemes(source: &Source, backend: Backend) -> Result<Schemes, Report> {
+pub fn generate_base16_schemes(
+    source: &Source,
+    backend: Backend,
+    region: &Region,
+) -> Result<Schemes, Report> {
     let schemes = match source {
         Source::Json { path: _ } => unreachable!(),
-        Source::Image { path } => {
-            let image = ImageReader::open(path)?
-                .with_guessed_format()?
-                .decode()?
-                .to_rgb8();
-            generate_base16_schemes_from_image(&image, backend).wrap_err(format!(
+        Source::Image { paths, .. } => {
+            let frames = region.open_all(&source.images()?)?;
+            generate_base16_schemes_from_image(&frames, backend).wrap_err(format!(
                 "Could not generate base16 scheme from image: {}",
-                path
+                paths.join(", ")
             ))?
         }
         Source::Color(color) => generate_base16_schemes_from_color(color).wrap_err(format!(
@@ -170,8 +172,8 @@ pub fn generate_base16_schemes(source: &Source, backend: Backend) -> Result<Sche
         #[cfg(feature = "web-image")]
         Source::WebImage { url } => {
             let bytes = reqwest::blocking::get(url)?.bytes()?;
-            let image = image::load_from_memory(&bytes)?.to_rgb8();
-            generate_base16_schemes_from_image(&image, backend).wrap_err(format!(
+            let image = region.apply(image::load_from_memory(&bytes)?)?;
+            generate_base16_schemes_from_image(&[image], backend).wrap_err(format!(
                 "Could not generate base16 scheme from image: {}",
                 url
             ))?
@@ -181,10 +183,14 @@ pub fn generate_base16_schemes(source: &Source, backend: Backend) -> Result<Sche
 }
 
 pub fn generate_base16_schemes_from_image(
-    image: &RgbImage,
+    frames: &[WeightedImage],
     backend: Backend,
 ) -> Result<Schemes, Report> {
-    let palette = backend.create().extract(&image);
+    let palette = backend.create().extract(frames);
+
+    if palette.is_empty() {
+        return Err(Report::msg("No colors could be extracted from the image"));
+    }
 
     let dark_scheme = generate_base16_scheme_from_palette(&palette, true)?;
     let light_scheme = generate_base16_scheme_from_palette(&palette, false)?;
@@ -4,28 +4,42 @@ use material_colors::{
     color::{Argb, Lab},
     dynamic_color::{DynamicScheme, MaterialDynamicColors, Variant as MaterialColorsVariant},
     hct::Cam16,
-    image::{FilterType, ImageReader},
-    quantize::{Quantizer, QuantizerCelebi},
+    image::FilterType,
+    quantize::{Quantizer, QuantizerCelebi, QuantizerResult, QuantizerWsmeans, QuantizerWu},
     score::Score,
     theme::{ColorGroup, CustomColor, CustomColorGroup},
 };
 
 use crate::{
-    color::math::{get_color_distance_lab, get_color_distance_lab_from_str, lightness, value},
+    color::math::{find_closest_color, get_color_distance_lab, lightness, value},
     scheme::SchemeTypes,
 };
 use crate::{
-    color::{format::rgb_from_argb, math::saturation},
+    color::{
+        format::{argb_from_rgb, rgb_from_argb},
+        math::saturation,
+        parse::parse_css_color,
+    },
     util::color::generate_style,
 };
-use crate::{util::arguments::SelectionPreference, FilterType as OwnFilterType};
+use crate::{
+    color::{
+        quantizer_kmeans::QuantizerKmeans, quantizer_median_cut::QuantizerMedianCut,
+        region::Region, stdin,
+    },
+    util::{
+        arguments::{AlphaMode, Cli, QuantizerType, SelectionPreference},
+        config::Config,
+    },
+    FilterType as OwnFilterType,
+};
 use color_eyre::{eyre::WrapErr, Report};
 use colorsys::{Hsl, Rgb};
+use image::DynamicImage;
+use indexmap::IndexMap;
 use owo_colors::OwoColorize;
 use std::{io::IsTerminal as _, str::FromStr};
 
-use material_colors::image::AsPixels;
-
 #[derive(clap::Parser, Debug, Clone)]
 pub enum ColorFormat {
     Hex { string: String },
@@ -45,14 +59,22 @@ impl ColorFormat {
 
 #[derive(clap::Subcommand, Debug, Clone)]
 pub enum Source {
-    /// The image to use for generating a color scheme
-    Image { path: String },
+    /// The image to use for generating a color scheme, the colors of several images are blended
+    /// into one scheme. `-` reads an image from stdin
+    Image {
+        #[arg(required = true, num_args = 1..)]
+        paths: Vec<String>,
+
+        /// How much each image counts, in the same order as the paths, for example `2,1`
+        #[arg(long, value_delimiter = ',')]
+        weights: Option<Vec<f64>>,
+    },
 
     #[cfg(feature = "web-image")]
     /// The image to fetch from web and use for generating a color scheme
     WebImage { url: String },
 
-    /// The source color to use for generating a color scheme
+    /// The source color to use for generating a color scheme, `-` reads it from stdin
     #[clap(subcommand)]
     Color(crate::color::color::ColorFormat),
 
@@ -60,14 +82,113 @@ pub enum Source {
     Json { path: String },
 }
 
+// The source given on the command line, a directory is resolved into the image picked from it
+// before anything else sees the source. Not a doc comment, clap would use it as the about text
+#[derive(clap::Subcommand, Debug, Clone)]
+pub enum SourceArg {
+    #[command(flatten)]
+    Source(Source),
+
+    /// Picks an image from a directory, then uses it like `image`
+    Directory {
+        path: String,
+
+        /// How the image is picked
+        #[arg(long, value_enum, default_value_t)]
+        pick: crate::util::arguments::PickMode,
+    },
+}
+
+impl SourceArg {
+    /// Picks the image of a directory source and resolves the source with `Source::resolve`.
+    pub fn resolve(self) -> Result<Source, Report> {
+        match self {
+            SourceArg::Source(source) => source.resolve(),
+            SourceArg::Directory { path, pick } => {
+                let image = crate::color::directory::pick_image(&path, pick)?;
+                info!("Picked <d><u>{}</> from <d><u>{}</>", image.display(), path);
+
+                Ok(Source::Image {
+                    paths: vec![image.to_string_lossy().into_owned()],
+                    weights: None,
+                })
+            }
+        }
+    }
+}
+
 impl Source {
+    /// Reads `-` from stdin, other sources are kept as is.
+    pub fn resolve(self) -> Result<Source, Report> {
+        match self {
+            Source::Image { paths, weights } => {
+                if paths.iter().filter(|path| *path == stdin::STDIN).count() > 1 {
+                    return Err(Report::msg("Stdin can only be used for one image"));
+                }
+
+                let paths = paths
+                    .into_iter()
+                    .map(|path| match path.as_str() {
+                        stdin::STDIN => {
+                            stdin::read_image().map(|path| path.to_string_lossy().into_owned())
+                        }
+                        _ => Ok(path),
+                    })
+                    .collect::<Result<Vec<String>, Report>>()?;
+
+                Ok(Source::Image { paths, weights })
+            }
+            Source::Color(color) if color.get_string() == stdin::STDIN => {
+                let string = stdin::read_color()?;
+
+                Ok(Source::Color(match color {
+                    ColorFormat::Hex { .. } => ColorFormat::Hex { string },
+                    ColorFormat::Rgb { .. } => ColorFormat::Rgb { string },
+                    ColorFormat::Hsl { .. } => ColorFormat::Hsl { string },
+                }))
+            }
+            _ => Ok(self),
+        }
+    }
+
     pub fn is_image(&self) -> bool {
         match self {
-            Source::Image { path: _ } => true,
+            Source::Image { .. } => true,
             _ => false,
         }
     }
 
+    /// The paths of an image source with their weights, which default to 1.
+    pub fn images(&self) -> Result<Vec<(&str, f64)>, Report> {
+        let Source::Image { paths, weights } = self else {
+            return Ok(Vec::new());
+        };
+
+        let weights = match weights {
+            Some(weights) if weights.len() != paths.len() => {
+                return Err(Report::msg(format!(
+                    "{} weights were given for {} images",
+                    weights.len(),
+                    paths.len()
+                )))
+            }
+            Some(weights) => weights.clone(),
+            None => vec![1.0; paths.len()],
+        };
+
+        if let Some(weight) = weights
+            .iter()
+            .find(|weight| weight.is_nan() || **weight <= 0.0)
+        {
+            return Err(Report::msg(format!(
+                "Image weights have to be larger than 0, got {}",
+                weight
+            )));
+        }
+
+        Ok(paths.iter().map(String::as_str).zip(weights).collect())
+    }
+
     pub fn is_json(&self) -> bool {
         match self {
             Source::Json { path: _ } => true,
@@ -82,6 +203,15 @@ pub struct ColorDefinition {
     pub color: String,
 }
 
+/// The colors a hook compares `compare_to` against, either a list or the name of a palette from
+/// `[config.palettes]`.
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum ColorsToCompare {
+    Colors(Vec<ColorDefinition>),
+    Palette(String),
+}
+
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone)]
 #[serde(untagged)]
 pub enum OwnCustomColor {
@@ -153,6 +283,144 @@ pub fn format_ranked(ranked: &[Argb]) -> Vec<String> {
         .collect()
 }
 
+/// How many times the pixels with the largest weight are counted.
+const WEIGHT_STEPS: usize = 16;
+
+/// How many times a pixel is counted for a weight between 0 and 1, relative to the largest
+/// weight. Pixels with any weight are counted at least once.
+fn weight_repeats(weight: f64) -> usize {
+    if weight <= 0.0 {
+        return 0;
+    }
+    ((weight * WEIGHT_STEPS as f64).round() as usize).max(1)
+}
+
+/// The number of pixels an image with a weight of 1 adds when several images are pooled.
+const POOLED_PIXELS: f64 = 112.0 * 112.0;
+
+/// How colors are extracted from an image, set with `--quantizer` and in `[config]`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtractionOptions {
+    pub quantizer: QuantizerType,
+    pub max_colors: usize,
+    /// Colors with a lower CAM16 chroma are ignored when scoring
+    pub chroma_threshold: f64,
+    pub region: Region,
+    pub alpha_mode: AlphaMode,
+    /// What semi-transparent pixels are composited over with `AlphaMode::Composite`
+    pub alpha_background: Argb,
+}
+
+impl Default for ExtractionOptions {
+    fn default() -> Self {
+        Self {
+            quantizer: QuantizerType::Celebi,
+            max_colors: 128,
+            chroma_threshold: 5.0,
+            region: Region::default(),
+            alpha_mode: AlphaMode::Opaque,
+            alpha_background: Argb::new(255, 255, 255, 255),
+        }
+    }
+}
+
+impl ExtractionOptions {
+    pub fn new(args: &Cli, config: &Config) -> Result<Self, Report> {
+        let default = Self::default();
+
+        let alpha_background = match args
+            .alpha_background
+            .as_ref()
+            .or(config.alpha_background.as_ref())
+        {
+            Some(color) => argb_from_rgb(
+                &parse_css_color(color)
+                    .wrap_err("Failed to parse the alpha_background string as a css color")?,
+            ),
+            None => default.alpha_background,
+        };
+
+        Ok(Self {
+            quantizer: args
+                .quantizer
+                .or(config.quantizer)
+                .unwrap_or(default.quantizer),
+            max_colors: config.max_colors.unwrap_or(default.max_colors).max(1),
+            chroma_threshold: config.chroma_threshold.unwrap_or(default.chroma_threshold),
+            region: Region {
+                crop: args.crop.or(config.crop),
+                margin: args.margin.or(config.margin),
+                center_weight: args
+                    .center_weight
+                    .or(config.center_weight)
+                    .unwrap_or(0.0)
+                    .max(0.0),
+                mask: args.mask.clone().or(config.mask.clone()),
+                frame_step: args.frame_step.or(config.frame_step).unwrap_or(1).max(1),
+            },
+            alpha_mode: args
+                .alpha_mode
+                .or(config.alpha_mode)
+                .unwrap_or(default.alpha_mode),
+            alpha_background,
+        })
+    }
+
+    /// Part of the cache file name, so that changing the options extracts the colors again.
+    pub fn cache_key(&self) -> String {
+        format!(
+            "{:?}.{}.{:?}.{}.{:?}.{}",
+            self.quantizer,
+            self.max_colors,
+            self.chroma_threshold,
+            self.region.cache_key(),
+            self.alpha_mode,
+            self.alpha_background.to_hex()
+        )
+    }
+
+    pub fn quantize(&self, pixels: &[Argb]) -> QuantizerResult {
+        match self.quantizer {
+            QuantizerType::Celebi => QuantizerCelebi::quantize(pixels, self.max_colors),
+            QuantizerType::Wu => {
+                count_nearest(pixels, QuantizerWu::quantize(pixels, self.max_colors))
+            }
+            QuantizerType::Wsmeans => QuantizerWsmeans::quantize(pixels, self.max_colors, &[]),
+            QuantizerType::Kmeans => QuantizerKmeans::quantize(pixels, self.max_colors),
+            QuantizerType::MedianCut => QuantizerMedianCut::quantize(pixels, self.max_colors),
+        }
+    }
+}
+
+/// Wu only returns the colors of its boxes, so every pixel is counted towards the closest one.
+fn count_nearest(pixels: &[Argb], mut result: QuantizerResult) -> QuantizerResult {
+    let clusters: Vec<(Argb, Lab)> = result
+        .color_to_count
+        .keys()
+        .map(|argb| (*argb, Lab::from(*argb)))
+        .collect();
+
+    for pixel in pixels {
+        let cluster = match result.input_pixel_to_cluster_pixel.get(pixel) {
+            Some(cluster) => *cluster,
+            None => {
+                let lab = Lab::from(*pixel);
+                let Some((cluster, _)) = clusters.iter().min_by(|(_, a), (_, b)| {
+                    get_color_distance_lab(&lab, a).total_cmp(&get_color_distance_lab(&lab, b))
+                }) else {
+                    break;
+                };
+                result.input_pixel_to_cluster_pixel.insert(*pixel, *cluster);
+                *cluster
+            }
+        };
+
+        *result.color_to_count.entry(cluster).or_insert(0) += 1;
+    }
+
+    result
+}
+
 pub fn get_filter(resize_filter: &Option<OwnFilterType>) -> FilterType {
     match resize_filter {
         Some(v) => FilterType::from(v),
@@ -166,22 +434,30 @@ pub fn get_source_color(
     fallback_color: Option<Argb>,
     prefer: &Option<SelectionPreference>,
     source_color_index: &Option<i64>,
+    options: &ExtractionOptions,
 ) -> Result<Argb, Report> {
     use crate::color::color;
 
     let filter = get_filter(resize_filter);
 
     let source_color: Argb = match source {
-        Source::Image { path } => {
-            info!("Opening image in <d><u>{}</>", path);
+        Source::Image { paths, .. } => {
+            let images = source.images()?;
+            for path in paths {
+                info!("Opening image in <d><u>{}</>", path);
+            }
             color::get_source_color_from_image(
-                path,
+                &images,
                 filter,
                 fallback_color,
                 &prefer,
                 source_color_index,
+                options,
             )
-            .wrap_err(format!("Could not get source color from image: {}", path))?
+            .wrap_err(format!(
+                "Could not get source color from image: {}",
+                paths.join(", ")
+            ))?
         }
         #[cfg(feature = "web-image")]
         Source::WebImage { url } => {
@@ -199,13 +475,14 @@ pub fn get_source_color(
 }
 
 pub fn get_source_color_from_image(
-    path: &str,
+    images: &[(&str, f64)],
     filter_type: FilterType,
     fallback_color: Option<Argb>,
     prefer: &Option<SelectionPreference>,
     source_color_index: &Option<i64>,
+    options: &ExtractionOptions,
 ) -> Result<Argb, Report> {
-    let ranked = get_scored_colors_from_image(path, filter_type, fallback_color)?;
+    let ranked = get_scored_colors_from_image(images, filter_type, fallback_color, options)?;
     let ranked_formatted = format_ranked(&ranked);
 
     debug!("Ranked colors:");
@@ -245,31 +522,135 @@ pub fn get_source_color_from_image(
     Ok(ranked[selection])
 }
 
-pub fn get_scored_colors_from_image(
+/// Handles transparency before the image is resized, so that transparent pixels don't bleed into
+/// the colors of their neighbours.
+fn prepare_alpha(image: &mut DynamicImage, options: &ExtractionOptions) {
+    if options.alpha_mode == AlphaMode::Opaque || !DynamicImage::color(image).has_alpha() {
+        return;
+    }
+
+    let mut rgba = image.to_rgba8();
+    let background = [
+        options.alpha_background.red,
+        options.alpha_background.green,
+        options.alpha_background.blue,
+    ];
+
+    for pixel in rgba.pixels_mut() {
+        let alpha = pixel[3] as f64 / 255.0;
+
+        for (channel, background) in pixel.0.iter_mut().take(3).zip(background) {
+            *channel = match options.alpha_mode {
+                AlphaMode::Composite => {
+                    (*channel as f64 * alpha + background as f64 * (1.0 - alpha)).round() as u8
+                }
+                // Premultiplied, undone in `alpha_pixel`
+                _ => (*channel as f64 * alpha).round() as u8,
+            };
+        }
+
+        if options.alpha_mode == AlphaMode::Composite {
+            pixel[3] = 255;
+        }
+    }
+
+    *image = DynamicImage::ImageRgba8(rgba);
+}
+
+/// The color of a resized pixel, or `None` if it should be left out.
+fn alpha_pixel([r, g, b, a]: [u8; 4], mode: AlphaMode) -> Option<Argb> {
+    match mode {
+        AlphaMode::Opaque | AlphaMode::Composite => (a == 255).then(|| Argb::new(a, r, g, b)),
+        AlphaMode::SkipTransparent if a == 0 => None,
+        AlphaMode::SkipTransparent => {
+            let unpremultiply = |c: u8| (c as f64 * 255.0 / a as f64).round().min(255.0) as u8;
+            Some(Argb::new(
+                255,
+                unpremultiply(r),
+                unpremultiply(g),
+                unpremultiply(b),
+            ))
+        }
+    }
+}
+
+/// Quantizes every sampled frame of an image together.
+fn quantize_image(
     path: &str,
     filter_type: FilterType,
+    options: &ExtractionOptions,
+) -> Result<QuantizerResult, Report> {
+    // Animations are pooled, so every sampled frame contributes equally
+    let mut pixels: Vec<Argb> = Vec::new();
+    for mut frame in options.region.open(path)? {
+        prepare_alpha(&mut frame.image, options);
+        let image = frame.resize_exact(112, 112, filter_type);
+        let rgba = image.image.to_rgba8();
+
+        for (x, y, pixel) in rgba.enumerate_pixels() {
+            let Some(argb) = alpha_pixel(pixel.0, options.alpha_mode) else {
+                continue;
+            };
+
+            if image.is_weighted() {
+                // Quantizers count pixels, so weights are applied by repeating them
+                let repeats = weight_repeats(image.weight(x, y) / image.max_weight());
+                pixels.extend(std::iter::repeat_n(argb, repeats));
+            } else {
+                pixels.push(argb);
+            }
+        }
+    }
+
+    let result = options.quantize(&pixels);
+    debug!(
+        "Quantized {} with {:?} into {} colors",
+        path,
+        options.quantizer,
+        result.color_to_count.len()
+    );
+
+    Ok(result)
+}
+
+/// Scores the colors of one or more weighted images together.
+pub fn get_scored_colors_from_image(
+    images: &[(&str, f64)],
+    filter_type: FilterType,
     fallback_color: Option<Argb>,
+    options: &ExtractionOptions,
 ) -> Result<Vec<Argb>, Report> {
-    let mut original = ImageReader::open(path)?;
-    let image = original.resize(112, 112, filter_type);
-    let pixels: Vec<Argb> = image
-        .as_pixels()
-        .iter()
-        .copied()
-        .filter(|argb| argb.alpha == 255)
-        .collect();
-    let mut result = QuantizerCelebi::quantize(&pixels, 128);
+    let mut pooled = None;
 
-    result
-        .color_to_count
-        .retain(|&argb, _| Cam16::from(argb).chroma >= 5.0);
+    for (path, weight) in images {
+        let mut result = quantize_image(path, filter_type, options)?;
 
-    Ok(Score::score(
-        &result.color_to_count,
-        None,
-        fallback_color,
-        None,
-    ))
+        if images.len() > 1 {
+            // Every image counts by its weight, no matter its size or how many frames it has
+            let total: u32 = result.color_to_count.values().sum();
+            for count in result.color_to_count.values_mut() {
+                *count =
+                    (*count as f64 / total.max(1) as f64 * weight * POOLED_PIXELS).round() as u32;
+            }
+        }
+
+        result
+            .color_to_count
+            .retain(|&argb, _| Cam16::from(argb).chroma >= options.chroma_threshold);
+
+        match &mut pooled {
+            None => pooled = Some(result.color_to_count),
+            Some(pooled) => {
+                for (argb, count) in result.color_to_count {
+                    *pooled.entry(argb).or_insert(0) += count;
+                }
+            }
+        }
+    }
+
+    let pooled = pooled.ok_or_else(|| Report::msg("No images were given"))?;
+
+    Ok(Score::score(&pooled, None, fallback_color, None))
 }
 
 pub fn select_source_color_from_ranks(
@@ -329,6 +710,8 @@ fn get_comparison_values(a: &Argb, b: &Argb, pref: &SelectionPreference) -> (f32
 #[cfg(feature = "web-image")]
 pub fn get_source_color_from_web_image(url: &str, filter_type: FilterType) -> Result<Argb, Report> {
     let bytes = reqwest::blocking::get(url)?.bytes()?;
+    use material_colors::image::ImageReader;
+
     Ok(ImageReader::extract_color(
         ImageReader::read(&bytes)?.resize(128, 128, filter_type),
     ))
@@ -408,33 +791,89 @@ pub fn make_custom_color(
     }
 }
 
+/// Parses the colors of a `[config.palettes]` entry or of `colors_to_compare`.
+pub fn palette_from_definitions(
+    definitions: &[ColorDefinition],
+) -> Result<IndexMap<String, Rgb>, Report> {
+    definitions
+        .iter()
+        .map(|definition| {
+            let color = parse_css_color(&definition.color)
+                .wrap_err(format!("Invalid color '{}'", definition.color))?;
+            Ok((definition.name.clone(), color))
+        })
+        .collect()
+}
+
 pub fn get_closest_color(
-    colors_to_compare: &Vec<ColorDefinition>,
+    palette: &IndexMap<String, Rgb>,
     compare_to: &str,
 ) -> Result<String, Report> {
-    let mut closest_distance: Option<f64> = None;
-    let mut closest_color: &str = "";
-
-    for c in colors_to_compare {
-        let distance = match get_color_distance_lab_from_str(&c.color, compare_to) {
-            Ok(v) => v,
-            Err(e) => {
-                error!(
-                    "Failed to get color distance between {} and {}",
-                    c.color, compare_to
-                );
-                return Err(Report::msg(format!("Could not get closest color: {}", e)));
-            }
+    let target = parse_css_color(compare_to).wrap_err(format!(
+        "Could not get the closest color to '{}'",
+        compare_to
+    ))?;
+
+    let closest_color = find_closest_color(palette.iter(), &target)
+        .cloned()
+        .unwrap_or_default();
+
+    debug!("closest color: {}", closest_color);
+    Ok(closest_color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::RgbaImage;
+
+    #[test]
+    fn keeps_pixels_with_small_weights() {
+        assert_eq!(weight_repeats(0.0), 0);
+        assert_eq!(weight_repeats(1.0), WEIGHT_STEPS);
+        // The corners of an image with a center weight of 8, and a mask value of 5%
+        assert_eq!(weight_repeats(1.0 / 9.0), 2);
+        assert_eq!(weight_repeats(0.05), 1);
+        assert_eq!(weight_repeats(1.0 / 255.0), 1);
+        assert!(weight_repeats(0.5) > weight_repeats(0.25));
+    }
+
+    #[test]
+    fn pairs_images_with_weights() {
+        let source = |weights: Option<Vec<f64>>| Source::Image {
+            paths: vec!["a.png".to_string(), "b.png".to_string()],
+            weights,
         };
-        if closest_distance.is_none() || closest_distance.unwrap() > distance {
-            closest_distance = Some(distance);
-            closest_color = &c.name;
-        }
-        debug!("distance: {}, name: {}", distance, c.name)
+
+        assert_eq!(
+            source(None).images().unwrap(),
+            vec![("a.png", 1.0), ("b.png", 1.0)]
+        );
+        assert_eq!(
+            source(Some(vec![2.0, 0.5])).images().unwrap(),
+            vec![("a.png", 2.0), ("b.png", 0.5)]
+        );
+        assert!(source(Some(vec![2.0])).images().is_err());
+        assert!(source(Some(vec![2.0, 0.0])).images().is_err());
+    }
+
+    #[test]
+    fn handles_transparent_pixels() {
+        let mut image =
+            DynamicImage::ImageRgba8(RgbaImage::from_pixel(1, 1, image::Rgba([200, 100, 0, 128])));
+        let options = ExtractionOptions {
+            alpha_mode: AlphaMode::Composite,
+            alpha_background: Argb::new(255, 0, 0, 0),
+            ..Default::default()
+        };
+        prepare_alpha(&mut image, &options);
+        assert_eq!(image.to_rgba8().get_pixel(0, 0).0, [100, 50, 0, 255]);
+
+        assert_eq!(alpha_pixel([200, 100, 0, 128], AlphaMode::Opaque), None);
+        assert_eq!(alpha_pixel([0, 0, 0, 0], AlphaMode::SkipTransparent), None);
+        assert_eq!(
+            alpha_pixel([100, 50, 0, 128], AlphaMode::SkipTransparent),
+            Some(Argb::new(255, 199, 100, 0))
+        );
     }
-    debug!(
-        "closest distance: {:?}, closest color: {}",
-        closest_distance, closest_color
-    );
-    Ok(closest_color.to_string())
 }
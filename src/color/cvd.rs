@@ -0,0 +1,148 @@
+use colorsys::Rgb;
+
+type Matrix = [[f64; 3]; 3];
+
+// Machado, Oliveira & Fernandes (2009), severity 1.0, applied in linear RGB
+const PROTANOPIA: Matrix = [
+    [0.152286, 1.052583, -0.204868],
+    [0.114503, 0.786281, 0.099216],
+    [-0.003882, -0.048116, 1.051998],
+];
+
+const DEUTERANOPIA: Matrix = [
+    [0.367322, 0.860646, -0.227968],
+    [0.280085, 0.672501, 0.047413],
+    [-0.011820, 0.042940, 0.968881],
+];
+
+const TRITANOPIA: Matrix = [
+    [1.255528, -0.076749, -0.178779],
+    [-0.078411, 0.930809, 0.147602],
+    [0.004733, 0.691367, 0.303900],
+];
+
+// Rec. 709 luminance
+const ACHROMATOPSIA: Matrix = [
+    [0.2126, 0.7152, 0.0722],
+    [0.2126, 0.7152, 0.0722],
+    [0.2126, 0.7152, 0.0722],
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deficiency {
+    Protanopia,
+    Deuteranopia,
+    Tritanopia,
+    Achromatopsia,
+}
+
+impl Deficiency {
+    pub const ALL: [Deficiency; 4] = [
+        Deficiency::Protanopia,
+        Deficiency::Deuteranopia,
+        Deficiency::Tritanopia,
+        Deficiency::Achromatopsia,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|d| d.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Deficiency::Protanopia => "protanopia",
+            Deficiency::Deuteranopia => "deuteranopia",
+            Deficiency::Tritanopia => "tritanopia",
+            Deficiency::Achromatopsia => "achromatopsia",
+        }
+    }
+
+    fn matrix(&self) -> &'static Matrix {
+        match self {
+            Deficiency::Protanopia => &PROTANOPIA,
+            Deficiency::Deuteranopia => &DEUTERANOPIA,
+            Deficiency::Tritanopia => &TRITANOPIA,
+            Deficiency::Achromatopsia => &ACHROMATOPSIA,
+        }
+    }
+}
+
+fn to_linear(channel: f64) -> f64 {
+    let c = channel / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn from_linear(channel: f64) -> f64 {
+    let c = channel.clamp(0.0, 1.0);
+    let c = if c <= 0.0031308 {
+        c * 12.92
+    } else {
+        1.055 * c.powf(1.0 / 2.4) - 0.055
+    };
+    (c * 255.0).round()
+}
+
+/// Simulates how a color is perceived with the given color vision deficiency, keeping its alpha.
+pub fn simulate(color: &Rgb, deficiency: Deficiency) -> Rgb {
+    let linear = [
+        to_linear(color.red()),
+        to_linear(color.green()),
+        to_linear(color.blue()),
+    ];
+    let m = deficiency.matrix();
+    let channel = |row: usize| {
+        from_linear(m[row][0] * linear[0] + m[row][1] * linear[1] + m[row][2] * linear[2])
+    };
+
+    let mut out = color.clone();
+    out.set_red(channel(0));
+    out.set_green(channel(1));
+    out.set_blue(channel(2));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn neutral_colors_are_unchanged() {
+        for deficiency in Deficiency::ALL {
+            for value in [0.0, 128.0, 255.0] {
+                let gray = Rgb::new(value, value, value, None);
+                let simulated = simulate(&gray, deficiency);
+
+                assert!((simulated.red() - value).abs() <= 1.0);
+                assert!((simulated.green() - value).abs() <= 1.0);
+                assert!((simulated.blue() - value).abs() <= 1.0);
+            }
+        }
+    }
+
+    #[test]
+    fn red_and_green_collapse_for_deuteranopia() {
+        let red = simulate(&Rgb::new(200.0, 60.0, 60.0, None), Deficiency::Deuteranopia);
+        let green = simulate(
+            &Rgb::new(110.0, 130.0, 60.0, None),
+            Deficiency::Deuteranopia,
+        );
+
+        assert!((red.red() - green.red()).abs() < 30.0);
+        assert!((red.green() - green.green()).abs() < 30.0);
+    }
+
+    #[test]
+    fn achromatopsia_is_grayscale() {
+        let simulated = simulate(
+            &Rgb::new(103.0, 80.0, 164.0, None),
+            Deficiency::Achromatopsia,
+        );
+
+        assert_eq!(simulated.red(), simulated.green());
+        assert_eq!(simulated.green(), simulated.blue());
+    }
+}
@@ -0,0 +1,203 @@
+use std::{
+    collections::hash_map::RandomState,
+    fs,
+    hash::BuildHasher,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use color_eyre::{eyre::WrapErr, Report};
+use image::ImageFormat;
+use sha2::{Digest, Sha256};
+
+use crate::util::{
+    arguments::PickMode,
+    config::{get_proj_path, ProjectDirsTypes},
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Picks one image from `dir`, returning its path.
+pub fn pick_image(dir: &str, mode: PickMode) -> Result<PathBuf, Report> {
+    let dir = fs::canonicalize(dir).wrap_err(format!("Could not find the directory {}", dir))?;
+    let mut images = list_images(&dir)?;
+
+    if images.is_empty() {
+        return Err(Report::msg(format!(
+            "The directory {} does not contain any images",
+            dir.display()
+        )));
+    }
+
+    // The last pick is stored for every mode, so `next` and `random` continue from it
+    let state = state_path(&dir)?;
+    let last = fs::read_to_string(&state).ok();
+    let last = last.as_deref().map(str::trim);
+
+    let index = match mode {
+        PickMode::Random => random_index(&images, last, random()),
+        PickMode::Next => next_index(&images, last),
+        PickMode::TimeOfDay => time_of_day_index(images.len(), local_seconds_of_day()),
+    };
+
+    if let Some(parent) = state.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&state, file_name(&images[index])).wrap_err(format!(
+        "Could not save the last image to {}",
+        state.display()
+    ))?;
+
+    debug!("Picked image {} of {}", index + 1, images.len());
+
+    Ok(images.swap_remove(index))
+}
+
+/// Every image directly inside `dir`, sorted by file name.
+fn list_images(dir: &Path) -> Result<Vec<PathBuf>, Report> {
+    let mut images: Vec<PathBuf> = fs::read_dir(dir)
+        .wrap_err(format!("Could not read the directory {}", dir.display()))?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file() && is_supported_image(path))
+        .collect();
+
+    images.sort_by_key(|path| file_name(path));
+
+    Ok(images)
+}
+
+fn is_supported_image(path: &Path) -> bool {
+    #[cfg(feature = "jxl-image")]
+    if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("jxl"))
+    {
+        return true;
+    }
+
+    ImageFormat::from_path(path).is_ok_and(|format| format.reading_enabled())
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// The file the last image picked from `dir` is stored in, one per directory.
+fn state_path(dir: &Path) -> Result<PathBuf, Report> {
+    let cache_folder = get_proj_path(&ProjectDirsTypes::Cache)
+        .ok_or_else(|| Report::msg("Could not find the cache directory"))?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(dir.to_string_lossy().as_bytes());
+
+    Ok(cache_folder
+        .join("directories")
+        .join(format!("{:x}", hasher.finalize())))
+}
+
+/// The first image sorted after `last`, wrapping around to the first image. Images can be added or
+/// removed between runs without restarting the sequence.
+fn next_index(images: &[PathBuf], last: Option<&str>) -> usize {
+    let Some(last) = last else {
+        return 0;
+    };
+
+    images
+        .iter()
+        .position(|path| file_name(path).as_str() > last)
+        .unwrap_or(0)
+}
+
+/// A random image other than `last`, unless it is the only image.
+fn random_index(images: &[PathBuf], last: Option<&str>, random: u64) -> usize {
+    let last = last.and_then(|last| images.iter().position(|path| file_name(path) == last));
+
+    match last {
+        Some(last) if images.len() > 1 => {
+            let index = (random % (images.len() - 1) as u64) as usize;
+            // Skip over the last image
+            index + usize::from(index >= last)
+        }
+        _ => (random % images.len() as u64) as usize,
+    }
+}
+
+fn random() -> u64 {
+    // Every `RandomState` gets new keys, seeded from the OS once per process
+    RandomState::new().hash_one(SystemTime::now())
+}
+
+/// Splits the day into `len` equally long parts, starting at midnight.
+fn time_of_day_index(len: usize, seconds_of_day: u64) -> usize {
+    ((seconds_of_day % SECONDS_PER_DAY) * len as u64 / SECONDS_PER_DAY) as usize
+}
+
+fn local_seconds_of_day() -> u64 {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+
+    (now as i64 + utc_offset(now as i64)).rem_euclid(SECONDS_PER_DAY as i64) as u64
+}
+
+/// The offset of the local timezone from UTC in seconds.
+#[cfg(unix)]
+fn utc_offset(time: i64) -> i64 {
+    let time = time as libc::time_t;
+    let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+
+    // SAFETY: both pointers are valid for the duration of the call
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return 0;
+    }
+
+    tm.tm_gmtoff as i64
+}
+
+#[cfg(not(unix))]
+fn utc_offset(_time: i64) -> i64 {
+    warn!("The local timezone is not supported on this platform, using UTC for time-of-day.");
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_in_sequence_and_by_time() {
+        let images: Vec<PathBuf> = ["a.png", "b.jpg", "c.webp"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+
+        assert_eq!(next_index(&images, None), 0);
+        assert_eq!(next_index(&images, Some("a.png")), 1);
+        assert_eq!(next_index(&images, Some("b.png")), 2);
+        assert_eq!(next_index(&images, Some("c.webp")), 0);
+
+        assert_eq!(time_of_day_index(3, 0), 0);
+        assert_eq!(time_of_day_index(3, 8 * 60 * 60), 1);
+        assert_eq!(time_of_day_index(3, SECONDS_PER_DAY - 1), 2);
+    }
+
+    #[test]
+    fn does_not_repeat_random_picks() {
+        let images: Vec<PathBuf> = ["a.png", "b.jpg", "c.webp"]
+            .iter()
+            .map(PathBuf::from)
+            .collect();
+
+        let picks: Vec<usize> = (0..6)
+            .map(|random| random_index(&images, Some("b.jpg"), random))
+            .collect();
+        assert!(!picks.contains(&1));
+        assert!(picks.contains(&0) && picks.contains(&2));
+
+        assert_eq!(random_index(&images[..1], Some("a.png"), 5), 0);
+        assert_eq!(random_index(&images, None, 4), 1);
+    }
+}
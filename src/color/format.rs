@@ -135,3 +135,239 @@ pub fn format_hsla(color: &Hsl) -> String {
         alpha
     )
 }
+
+pub fn alpha_f64(alpha: f64) -> f64 {
+    alpha_u8(alpha) as f64 / 255.0
+}
+
+// Appends ` / <alpha>` for the space separated css functions, only when the color is not opaque.
+fn css_alpha_suffix(color: &Rgb) -> String {
+    let alpha = alpha_f64(color.alpha());
+    if alpha < 1.0 {
+        format!(" / {}", (alpha * 100.0).round() / 100.0)
+    } else {
+        String::new()
+    }
+}
+
+fn round_to(value: f64, decimals: i32) -> f64 {
+    let factor = 10f64.powi(decimals);
+    (value * factor).round() / factor
+}
+
+pub fn format_argb_hex(color: &Rgb) -> String {
+    format!("0x{}", format_alpha_hex_stripped(color))
+}
+
+pub fn format_argb_int(color: &Rgb) -> i64 {
+    let alpha = alpha_u8(color.alpha()) as i64;
+    (alpha << 24)
+        | ((color.red() as u8 as i64) << 16)
+        | ((color.green() as u8 as i64) << 8)
+        | color.blue() as u8 as i64
+}
+
+pub fn format_rgb_float(color: &Rgb) -> String {
+    format!(
+        "{:.3}, {:.3}, {:.3}",
+        color.red() / 255.0,
+        color.green() / 255.0,
+        color.blue() / 255.0,
+    )
+}
+
+pub fn format_rgba_float(color: &Rgb) -> String {
+    format!(
+        "{}, {:.3}",
+        format_rgb_float(color),
+        alpha_f64(color.alpha())
+    )
+}
+
+pub fn format_rgb_percent(color: &Rgb) -> String {
+    format!(
+        "rgb({}% {}% {}%{})",
+        round_to(color.red() / 255.0 * 100.0, 1),
+        round_to(color.green() / 255.0 * 100.0, 1),
+        round_to(color.blue() / 255.0 * 100.0, 1),
+        css_alpha_suffix(color),
+    )
+}
+
+pub fn format_hwb(color: &Rgb) -> String {
+    let hsl = Hsl::from(color);
+    let max = color.red().max(color.green()).max(color.blue()) / 255.0;
+    let min = color.red().min(color.green()).min(color.blue()) / 255.0;
+
+    format!(
+        "hwb({:.0} {:.0}% {:.0}%{})",
+        hsl.hue(),
+        min * 100.0,
+        (1.0 - max) * 100.0,
+        css_alpha_suffix(color),
+    )
+}
+
+/// CIELAB with a D50 white point, like the css `lab()` function.
+pub fn lab_from_rgb(color: &Rgb) -> (f64, f64, f64) {
+    let linear = |c: f64| {
+        let c = c / 255.0;
+        if c <= 0.04045 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let (r, g, b) = (
+        linear(color.red()),
+        linear(color.green()),
+        linear(color.blue()),
+    );
+
+    // Linear sRGB to XYZ D50 (Bradford adapted)
+    let x = 0.4360747 * r + 0.3850649 * g + 0.1430804 * b;
+    let y = 0.2225045 * r + 0.7168786 * g + 0.0606169 * b;
+    let z = 0.0139322 * r + 0.0971045 * g + 0.7141733 * b;
+
+    let f = |t: f64| {
+        if t > 216.0 / 24389.0 {
+            t.cbrt()
+        } else {
+            (24389.0 / 27.0 * t + 16.0) / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x / 0.96422), f(y), f(z / 0.82521));
+
+    (116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+}
+
+pub fn lch_from_rgb(color: &Rgb) -> (f64, f64, f64) {
+    let (l, a, b) = lab_from_rgb(color);
+    let hue = b.atan2(a).to_degrees().rem_euclid(360.0);
+
+    (l, (a * a + b * b).sqrt(), hue)
+}
+
+pub fn format_lab(color: &Rgb) -> String {
+    let (l, a, b) = lab_from_rgb(color);
+
+    format!(
+        "lab({}% {} {}{})",
+        round_to(l, 2),
+        round_to(a, 2),
+        round_to(b, 2),
+        css_alpha_suffix(color),
+    )
+}
+
+pub fn format_lch(color: &Rgb) -> String {
+    let (l, c, h) = lch_from_rgb(color);
+
+    format!(
+        "lch({}% {} {}{})",
+        round_to(l, 2),
+        round_to(c, 2),
+        round_to(h, 2),
+        css_alpha_suffix(color),
+    )
+}
+
+/// Closest color in the xterm 256 color palette, ignoring the 16 terminal colors as those can be themed.
+pub fn format_ansi256(color: &Rgb) -> i64 {
+    const LEVELS: [f64; 6] = [0.0, 95.0, 135.0, 175.0, 215.0, 255.0];
+
+    let distance = |r: f64, g: f64, b: f64| {
+        (color.red() - r).powi(2) + (color.green() - g).powi(2) + (color.blue() - b).powi(2)
+    };
+    let nearest_level = |c: f64| {
+        (0..6)
+            .min_by(|a, b| (LEVELS[*a] - c).abs().total_cmp(&(LEVELS[*b] - c).abs()))
+            .unwrap()
+    };
+
+    let (r, g, b) = (
+        nearest_level(color.red()),
+        nearest_level(color.green()),
+        nearest_level(color.blue()),
+    );
+    let cube_index = 16 + 36 * r + 6 * g + b;
+    let cube_distance = distance(LEVELS[r], LEVELS[g], LEVELS[b]);
+
+    let average = (color.red() + color.green() + color.blue()) / 3.0;
+    let gray = (((average - 8.0) / 10.0).round() as i64).clamp(0, 23);
+    let gray_level = 8.0 + gray as f64 * 10.0;
+    let gray_distance = distance(gray_level, gray_level, gray_level);
+
+    if gray_distance < cube_distance {
+        232 + gray
+    } else {
+        cube_index as i64
+    }
+}
+
+pub fn format_xterm(color: &Rgb) -> String {
+    format!(
+        "rgb:{:02x}/{:02x}/{:02x}",
+        color.red() as u8,
+        color.green() as u8,
+        color.blue() as u8,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_lab_and_lch() {
+        assert_eq!(
+            format_lab(&Rgb::from_hex_str("#ff0000").unwrap()),
+            "lab(54.29% 80.81 69.89)"
+        );
+        assert_eq!(
+            format_lch(&Rgb::from_hex_str("#ff0000").unwrap()),
+            "lch(54.29% 106.84 40.85)"
+        );
+        assert_eq!(
+            format_lab(&Rgb::from_hex_str("#ffffff").unwrap()),
+            "lab(100% 0 0)"
+        );
+        assert_eq!(
+            format_lab(&Rgb::from_hex_str("#000000").unwrap()),
+            "lab(0% 0 0)"
+        );
+    }
+
+    #[test]
+    fn formats_hwb() {
+        assert_eq!(
+            format_hwb(&Rgb::from_hex_str("#ff0000").unwrap()),
+            "hwb(0 0% 0%)"
+        );
+        assert_eq!(
+            format_hwb(&Rgb::from_hex_str("#808080").unwrap()),
+            "hwb(0 50% 50%)"
+        );
+    }
+
+    #[test]
+    fn finds_ansi256_indices() {
+        assert_eq!(format_ansi256(&Rgb::from_hex_str("#ff0000").unwrap()), 196);
+        assert_eq!(format_ansi256(&Rgb::from_hex_str("#000000").unwrap()), 16);
+        assert_eq!(format_ansi256(&Rgb::from_hex_str("#ffffff").unwrap()), 231);
+        assert_eq!(format_ansi256(&Rgb::from_hex_str("#808080").unwrap()), 244);
+    }
+
+    #[test]
+    fn formats_xterm_and_argb() {
+        assert_eq!(
+            format_xterm(&Rgb::from_hex_str("#ff8000").unwrap()),
+            "rgb:ff/80/00"
+        );
+
+        let mut translucent = Rgb::from_hex_str("#6750a4").unwrap();
+        translucent.set_alpha(0.5);
+        assert_eq!(format_argb_int(&translucent), 0x806750a4);
+        assert_eq!(format_argb_hex(&translucent), "0x806750A4");
+    }
+}
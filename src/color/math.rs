@@ -1,7 +1,7 @@
-use color_eyre::Report;
 use colorsys::Rgb;
-use material_colors::color::{Argb, Lab};
-use std::str::FromStr;
+use material_colors::color::Lab;
+
+use crate::color::format::argb_from_rgb;
 
 pub fn get_color_distance_lab(c1: &Lab, c2: &Lab) -> f64 {
     let l: f64 = c1.l - c2.l;
@@ -11,11 +11,21 @@ pub fn get_color_distance_lab(c1: &Lab, c2: &Lab) -> f64 {
     f64::sqrt((l * l) + (a * a) + (b * b))
 }
 
-pub fn get_color_distance_lab_from_str(c1: &str, c2: &str) -> Result<f64, Report> {
-    let c1 = Lab::from(Argb::from_str(c1)?);
-    let c2 = Lab::from(Argb::from_str(c2)?);
+/// Finds the color closest to `target` by their distance in Lab, returning its key.
+pub fn find_closest_color<'a, K>(
+    colors: impl IntoIterator<Item = (K, &'a Rgb)>,
+    target: &Rgb,
+) -> Option<K> {
+    let target = Lab::from(argb_from_rgb(target));
 
-    Ok(get_color_distance_lab(&c1, &c2))
+    colors
+        .into_iter()
+        .map(|(key, color)| {
+            let distance = get_color_distance_lab(&target, &Lab::from(argb_from_rgb(color)));
+            (key, distance)
+        })
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(key, _)| key)
 }
 
 pub fn luminance(c: &Rgb) -> f32 {
@@ -41,3 +51,56 @@ pub fn value(c: &Rgb) -> f32 {
     let max = c.red().max(c.green()).max(c.blue()) as f32;
     max * (100.0 / 255.0)
 }
+
+/// Composites `top` over `bottom` at the given opacity, keeping the alpha of `bottom`.
+pub fn blend_over(top: &Rgb, bottom: &Rgb, opacity: f64) -> Rgb {
+    let opacity = opacity.clamp(0.0, 1.0);
+    let mix = |t: f64, b: f64| t * opacity + b * (1.0 - opacity);
+
+    let mut out = bottom.clone();
+    out.set_red(mix(top.red(), bottom.red()));
+    out.set_green(mix(top.green(), bottom.green()));
+    out.set_blue(mix(top.blue(), bottom.blue()));
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use colorsys::ColorAlpha;
+
+    use super::*;
+
+    #[test]
+    fn finds_the_closest_color() {
+        let colors = [
+            ("red", Rgb::from_hex_str("#ff0000").unwrap()),
+            ("green", Rgb::from_hex_str("#00ff00").unwrap()),
+            ("blue", Rgb::from_hex_str("#0000ff").unwrap()),
+        ];
+        let closest = |hex: &str| {
+            find_closest_color(
+                colors.iter().map(|(name, color)| (*name, color)),
+                &Rgb::from_hex_str(hex).unwrap(),
+            )
+        };
+
+        assert_eq!(closest("#e25252"), Some("red"));
+        assert_eq!(closest("#87b158"), Some("green"));
+        assert_eq!(closest("#5294e2"), Some("blue"));
+        assert_eq!(find_closest_color::<&str>([], &Rgb::default()), None);
+    }
+
+    #[test]
+    fn blends_at_the_given_opacity() {
+        let top = Rgb::from_hex_str("#ffffff").unwrap();
+        let mut bottom = Rgb::from_hex_str("#6750a4").unwrap();
+        bottom.set_alpha(0.5);
+
+        let blended = blend_over(&top, &bottom, 0.12);
+        assert_eq!(blended.to_hex_string(), "#7965af");
+        assert_eq!(blended.alpha(), 0.5);
+
+        assert_eq!(blend_over(&top, &bottom, 0.0).to_hex_string(), "#6750a4");
+        assert_eq!(blend_over(&top, &bottom, 2.0).to_hex_string(), "#ffffff");
+    }
+}
@@ -1,6 +1,12 @@
 pub mod backend;
 pub mod base16;
 pub mod color;
+pub mod cvd;
+pub mod directory;
 pub mod format;
 pub mod math;
 pub mod parse;
+pub mod quantizer_kmeans;
+pub mod quantizer_median_cut;
+pub mod region;
+pub mod stdin;
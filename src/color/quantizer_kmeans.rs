@@ -1 +1,166 @@
-// This file is intentionally empty - module was removed.
\ No newline at end of file
+use std::collections::HashMap;
+
+use material_colors::{
+    color::{Argb, Lab},
+    quantize::{PointProvider, PointProviderLab, Quantizer, QuantizerResult},
+};
+
+const MAX_ITERATIONS: usize = 10;
+
+/// Plain k-means in Lab space, weighted by how often each color appears.
+///
+/// Unlike Wsmeans it is seeded with k-means++ instead of random points, always taking the
+/// farthest color as the next centroid so that the result is deterministic. The dominant color
+/// of an image is always the first centroid, so large areas of a single hue keep their own
+/// cluster.
+pub struct QuantizerKmeans;
+
+impl Quantizer for QuantizerKmeans {
+    fn quantize(pixels: &[Argb], max_colors: usize) -> QuantizerResult {
+        let mut counts: Vec<(Argb, u32)> = Vec::new();
+        let mut indices: HashMap<Argb, usize> = HashMap::new();
+
+        for pixel in pixels {
+            let index = *indices.entry(*pixel).or_insert_with(|| {
+                counts.push((*pixel, 0));
+                counts.len() - 1
+            });
+            counts[index].1 += 1;
+        }
+
+        let points: Vec<Lab> = counts
+            .iter()
+            .map(|(argb, _)| PointProviderLab::lab_from_int(argb))
+            .collect();
+        let weights: Vec<f64> = counts.iter().map(|(_, count)| *count as f64).collect();
+
+        let mut centroids = seed_centroids(&points, &weights, max_colors);
+        let mut assignments = vec![usize::MAX; points.len()];
+
+        for _ in 0..MAX_ITERATIONS {
+            let mut changed = false;
+
+            for (point, assignment) in points.iter().zip(assignments.iter_mut()) {
+                let nearest = nearest_centroid(point, &centroids);
+                if nearest != *assignment {
+                    *assignment = nearest;
+                    changed = true;
+                }
+            }
+
+            if !changed {
+                break;
+            }
+
+            let mut sums = vec![(0.0, 0.0, 0.0, 0.0); centroids.len()];
+            for ((point, weight), assignment) in points.iter().zip(&weights).zip(&assignments) {
+                let sum = &mut sums[*assignment];
+                sum.0 += point.l * weight;
+                sum.1 += point.a * weight;
+                sum.2 += point.b * weight;
+                sum.3 += weight;
+            }
+
+            for (centroid, (l, a, b, total)) in centroids.iter_mut().zip(sums) {
+                if total > 0.0 {
+                    *centroid = Lab {
+                        l: l / total,
+                        a: a / total,
+                        b: b / total,
+                    };
+                }
+            }
+        }
+
+        let cluster_colors: Vec<Argb> =
+            centroids.iter().map(PointProviderLab::lab_to_int).collect();
+
+        let mut result = QuantizerResult {
+            color_to_count: Default::default(),
+            input_pixel_to_cluster_pixel: Default::default(),
+        };
+
+        for ((pixel, count), assignment) in counts.iter().zip(&assignments) {
+            let color = cluster_colors[*assignment];
+            *result.color_to_count.entry(color).or_insert(0) += count;
+            result.input_pixel_to_cluster_pixel.insert(*pixel, color);
+        }
+
+        result
+    }
+}
+
+fn nearest_centroid(point: &Lab, centroids: &[Lab]) -> usize {
+    centroids
+        .iter()
+        .map(|centroid| PointProviderLab::distance(point, centroid))
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+/// Picks up to `max_colors` starting centroids with a deterministic k-means++.
+fn seed_centroids(points: &[Lab], weights: &[f64], max_colors: usize) -> Vec<Lab> {
+    let mut centroids = Vec::new();
+
+    let Some(first) = weights
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .map(|(i, _)| i)
+    else {
+        return centroids;
+    };
+    centroids.push(points[first]);
+
+    let mut distances: Vec<f64> = points
+        .iter()
+        .map(|point| PointProviderLab::distance(point, &points[first]))
+        .collect();
+
+    while centroids.len() < max_colors {
+        let Some((next, score)) = distances
+            .iter()
+            .zip(weights)
+            .map(|(distance, weight)| distance * weight)
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        else {
+            break;
+        };
+
+        // Every color already is a centroid
+        if score <= 0.0 {
+            break;
+        }
+
+        let centroid = points[next];
+        centroids.push(centroid);
+
+        for (point, distance) in points.iter().zip(distances.iter_mut()) {
+            *distance = distance.min(PointProviderLab::distance(point, &centroid));
+        }
+    }
+
+    centroids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_dominant_color() {
+        let red = Argb::new(255, 200, 30, 30);
+        let blue = Argb::new(255, 30, 30, 200);
+        let mut pixels = vec![red; 90];
+        pixels.extend([blue; 10]);
+
+        let result = QuantizerKmeans::quantize(&pixels, 2);
+
+        assert_eq!(result.color_to_count.len(), 2);
+        assert_eq!(result.color_to_count.get(&red), Some(&90));
+        assert_eq!(result.color_to_count.get(&blue), Some(&10));
+    }
+}
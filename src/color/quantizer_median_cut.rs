@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+
+use material_colors::{
+    color::Argb,
+    quantize::{Quantizer, QuantizerResult},
+};
+
+/// A group of distinct colors and how often each appears.
+struct ColorBox {
+    colors: Vec<(Argb, u32)>,
+}
+
+impl ColorBox {
+    fn channel(color: &Argb, channel: usize) -> u8 {
+        match channel {
+            0 => color.red,
+            1 => color.green,
+            _ => color.blue,
+        }
+    }
+
+    /// The channel with the largest range and that range.
+    fn widest_channel(&self) -> (usize, u8) {
+        (0..3)
+            .map(|channel| {
+                let values = self.colors.iter().map(|(c, _)| Self::channel(c, channel));
+                let min = values.clone().min().unwrap_or(0);
+                let max = values.max().unwrap_or(0);
+                (channel, max - min)
+            })
+            .max_by_key(|(_, range)| *range)
+            .unwrap_or((0, 0))
+    }
+
+    fn population(&self) -> u64 {
+        self.colors.iter().map(|(_, count)| *count as u64).sum()
+    }
+
+    /// Splits the box at the weighted median of its widest channel.
+    fn split(mut self) -> (ColorBox, ColorBox) {
+        let (channel, _) = self.widest_channel();
+        self.colors
+            .sort_by_key(|(color, _)| Self::channel(color, channel));
+
+        let half = self.population() / 2;
+        let mut seen = 0;
+        let mut at = self.colors.len() - 1;
+
+        for (i, (_, count)) in self.colors.iter().enumerate() {
+            seen += *count as u64;
+            if seen >= half {
+                at = i + 1;
+                break;
+            }
+        }
+
+        // Both halves need at least one color
+        let at = at.clamp(1, self.colors.len() - 1);
+        let rest = self.colors.split_off(at);
+
+        (self, ColorBox { colors: rest })
+    }
+
+    fn average(&self) -> Argb {
+        let total = self.population().max(1) as f64;
+        let mean = |channel: usize| {
+            let sum: f64 = self
+                .colors
+                .iter()
+                .map(|(color, count)| Self::channel(color, channel) as f64 * *count as f64)
+                .sum();
+            (sum / total).round() as u8
+        };
+
+        Argb::new(255, mean(0), mean(1), mean(2))
+    }
+}
+
+/// Median cut, which keeps splitting the group of colors with the most pixels and spread until
+/// there are `max_colors` groups, then uses the average of each group.
+///
+/// Because large groups are split first, the dominant hues of an image end up as several
+/// colors while small accents are merged into their surroundings.
+pub struct QuantizerMedianCut;
+
+impl Quantizer for QuantizerMedianCut {
+    fn quantize(pixels: &[Argb], max_colors: usize) -> QuantizerResult {
+        let mut counts: HashMap<Argb, u32> = HashMap::new();
+        for pixel in pixels {
+            *counts.entry(*pixel).or_insert(0) += 1;
+        }
+
+        let mut colors: Vec<(Argb, u32)> = counts.into_iter().collect();
+        colors.sort_by_key(|(color, _)| (color.red, color.green, color.blue));
+
+        let mut boxes = vec![ColorBox { colors }];
+
+        while boxes.len() < max_colors {
+            let Some((index, _)) = boxes
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| b.colors.len() > 1)
+                .map(|(i, b)| (i, b.population() * b.widest_channel().1 as u64))
+                .max_by_key(|(_, score)| *score)
+            else {
+                break;
+            };
+
+            let (a, b) = boxes.swap_remove(index).split();
+            boxes.push(a);
+            boxes.push(b);
+        }
+
+        let mut result = QuantizerResult {
+            color_to_count: Default::default(),
+            input_pixel_to_cluster_pixel: Default::default(),
+        };
+
+        for color_box in boxes.iter().filter(|b| !b.colors.is_empty()) {
+            let average = color_box.average();
+            *result.color_to_count.entry(average).or_insert(0) += color_box.population() as u32;
+
+            for (color, _) in &color_box.colors {
+                result.input_pixel_to_cluster_pixel.insert(*color, average);
+            }
+        }
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_the_largest_groups() {
+        let mut pixels = vec![Argb::new(255, 10, 120, 10); 50];
+        pixels.extend([Argb::new(255, 20, 140, 20); 50]);
+        pixels.extend([Argb::new(255, 250, 0, 0); 2]);
+
+        let result = QuantizerMedianCut::quantize(&pixels, 2);
+
+        assert_eq!(result.color_to_count.len(), 2);
+        assert_eq!(
+            result.color_to_count.get(&Argb::new(255, 15, 130, 15)),
+            Some(&100)
+        );
+        assert_eq!(
+            result.color_to_count.get(&Argb::new(255, 250, 0, 0)),
+            Some(&2)
+        );
+    }
+}
@@ -0,0 +1,499 @@
+use std::{
+    fmt,
+    fs::{self, File},
+    io::BufReader,
+    path::PathBuf,
+    str::FromStr,
+};
+
+use color_eyre::{eyre::WrapErr, Report};
+use image::{
+    codecs::{gif::GifDecoder, png::PngDecoder, webp::WebPDecoder},
+    imageops::FilterType,
+    AnimationDecoder, DynamicImage, Frames, GenericImageView, GrayImage, ImageFormat, ImageReader,
+};
+use sha2::{Digest, Sha256};
+
+/// At most this many frames of an animation are sampled.
+const MAX_FRAMES: usize = 32;
+
+/// A length in pixels, or a percentage of the image size when it ends in `%`.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Length {
+    Pixels(u32),
+    Percent(f64),
+}
+
+impl Length {
+    fn resolve(&self, size: u32) -> u32 {
+        match self {
+            Length::Pixels(pixels) => (*pixels).min(size),
+            Length::Percent(percent) => ((size as f64 * percent / 100.0).round() as u32).min(size),
+        }
+    }
+}
+
+impl FromStr for Length {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+
+        match s.strip_suffix('%') {
+            Some(percent) => match percent.trim().parse::<f64>() {
+                Ok(percent) if (0.0..=100.0).contains(&percent) => Ok(Length::Percent(percent)),
+                _ => Err(format!("`{s}` isn't a percentage from 0% to 100%")),
+            },
+            None => s
+                .parse()
+                .map(Length::Pixels)
+                .map_err(|_| format!("`{s}` isn't a number of pixels or a percentage")),
+        }
+    }
+}
+
+impl TryFrom<String> for Length {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for Length {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Length::Pixels(pixels) => write!(f, "{}", pixels),
+            Length::Percent(percent) => write!(f, "{}%", percent),
+        }
+    }
+}
+
+impl From<Length> for String {
+    fn from(value: Length) -> Self {
+        value.to_string()
+    }
+}
+
+/// A rectangle written as `X,Y,WIDTH,HEIGHT`, for example `0,0,50%,100%` for the left half.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Crop {
+    pub x: Length,
+    pub y: Length,
+    pub width: Length,
+    pub height: Length,
+}
+
+impl FromStr for Crop {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s
+            .split(',')
+            .map(Length::from_str)
+            .collect::<Result<Vec<Length>, String>>()?;
+
+        match parts[..] {
+            [x, y, width, height] => Ok(Crop {
+                x,
+                y,
+                width,
+                height,
+            }),
+            _ => Err(format!("`{s}` should be written as X,Y,WIDTH,HEIGHT")),
+        }
+    }
+}
+
+impl TryFrom<String> for Crop {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for Crop {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{},{}", self.x, self.y, self.width, self.height)
+    }
+}
+
+impl From<Crop> for String {
+    fn from(value: Crop) -> Self {
+        value.to_string()
+    }
+}
+
+/// Which part of an image colors are extracted from, and how much each pixel counts.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Region {
+    pub crop: Option<Crop>,
+    /// Ignored on every side after cropping, for letterboxed images
+    pub margin: Option<Length>,
+    /// How much more the centre counts than the corners, 0 counts every pixel the same
+    pub center_weight: f64,
+    /// A grayscale image stretched over the whole image, black pixels are ignored and white
+    /// pixels count fully
+    pub mask: Option<PathBuf>,
+    /// At least this many frames apart are the sampled frames of an animated image, long
+    /// animations use a larger step
+    pub frame_step: usize,
+}
+
+impl Region {
+    /// The area left after the crop and the margin, as `(x, y, width, height)`.
+    fn bounds(&self, width: u32, height: u32) -> (u32, u32, u32, u32) {
+        let (mut x, mut y, mut w, mut h) = match &self.crop {
+            Some(crop) => {
+                let x = crop.x.resolve(width);
+                let y = crop.y.resolve(height);
+                (
+                    x,
+                    y,
+                    crop.width.resolve(width).min(width - x),
+                    crop.height.resolve(height).min(height - y),
+                )
+            }
+            None => (0, 0, width, height),
+        };
+
+        if let Some(margin) = &self.margin {
+            let mx = margin.resolve(w).min(w / 2);
+            let my = margin.resolve(h).min(h / 2);
+            x += mx;
+            y += my;
+            w -= mx * 2;
+            h -= my * 2;
+        }
+
+        (x, y, w, h)
+    }
+
+    /// Opens an image, with one entry for every sampled frame if it is animated.
+    pub fn open(&self, path: &str) -> Result<Vec<WeightedImage>, Report> {
+        let mask = self.load_mask()?;
+
+        decode_frames(path, self.frame_step.max(1))?
+            .into_iter()
+            .map(|frame| self.apply_with_mask(frame, mask.as_ref()))
+            .collect()
+    }
+
+    /// Opens the frames of every image, scaled so that each image counts by its weight no matter
+    /// how many frames it has.
+    pub fn open_all(&self, images: &[(&str, f64)]) -> Result<Vec<WeightedImage>, Report> {
+        let mut all = Vec::new();
+
+        for (path, weight) in images {
+            let frames = self.open(path)?;
+            let scale = weight / frames.len() as f64;
+            all.extend(
+                frames
+                    .into_iter()
+                    .map(|frame| WeightedImage { scale, ..frame }),
+            );
+        }
+
+        Ok(all)
+    }
+
+    pub fn apply(&self, image: DynamicImage) -> Result<WeightedImage, Report> {
+        self.apply_with_mask(image, self.load_mask()?.as_ref())
+    }
+
+    fn load_mask(&self) -> Result<Option<GrayImage>, Report> {
+        self.mask
+            .as_ref()
+            .map(|path| {
+                Ok(ImageReader::open(path)
+                    .wrap_err(format!("Could not open the mask {}", path.display()))?
+                    .with_guessed_format()?
+                    .decode()?
+                    .to_luma8())
+            })
+            .transpose()
+    }
+
+    /// Like `apply`, with a mask that was already loaded with `load_mask`.
+    fn apply_with_mask(
+        &self,
+        image: DynamicImage,
+        mask: Option<&GrayImage>,
+    ) -> Result<WeightedImage, Report> {
+        let (width, height) = image.dimensions();
+        let (x, y, w, h) = self.bounds(width, height);
+
+        if w == 0 || h == 0 {
+            return Err(Report::msg(format!(
+                "The crop and margin leave nothing of the {}x{} image",
+                width, height
+            )));
+        }
+
+        let mask = mask.map(|mask| {
+            let mask = image::imageops::resize(mask, width, height, FilterType::Triangle);
+            image::imageops::crop_imm(&mask, x, y, w, h).to_image()
+        });
+
+        Ok(WeightedImage {
+            image: image.crop_imm(x, y, w, h),
+            mask,
+            center_weight: self.center_weight,
+            scale: 1.0,
+        })
+    }
+
+    /// Part of the cache file name, the mask is included by the hash of its contents.
+    pub fn cache_key(&self) -> String {
+        let mask = self.mask.as_ref().map(|path| {
+            let bytes =
+                fs::read(path).unwrap_or_else(|_| path.to_string_lossy().as_bytes().to_vec());
+            format!("{:x}", Sha256::digest(bytes))[..12].to_string()
+        });
+
+        format!(
+            "{}.{}.{:?}.{}.{}",
+            self.crop.map_or("none".to_string(), |c| c.to_string()),
+            self.margin.map_or("none".to_string(), |m| m.to_string()),
+            self.center_weight,
+            mask.as_deref().unwrap_or("none"),
+            self.frame_step.max(1)
+        )
+    }
+}
+
+/// Evenly spaced frames of GIF, APNG and animated WebP images, at least `step` frames apart and at
+/// most `MAX_FRAMES` of them, or the only frame of other images.
+fn decode_frames(path: &str, step: usize) -> Result<Vec<DynamicImage>, Report> {
+    let reader = ImageReader::open(path)?.with_guessed_format()?;
+    let file = || File::open(path).map(BufReader::new);
+
+    let frames: Frames = match reader.format() {
+        Some(ImageFormat::Gif) => GifDecoder::new(file()?)?.into_frames(),
+        Some(ImageFormat::Png) => {
+            let decoder = PngDecoder::new(file()?)?;
+            if !decoder.is_apng()? {
+                return Ok(vec![reader.decode()?]);
+            }
+            decoder.apng()?.into_frames()
+        }
+        Some(ImageFormat::WebP) => {
+            let decoder = WebPDecoder::new(file()?)?;
+            if !decoder.has_animation() {
+                return Ok(vec![reader.decode()?]);
+            }
+            decoder.into_frames()
+        }
+        _ => return Ok(vec![reader.decode()?]),
+    };
+
+    // The number of frames isn't known up front, so whenever there are too many the step is
+    // doubled and every other sampled frame dropped, which keeps them evenly spaced
+    let mut step = step;
+    let mut sampled = Vec::new();
+    for (i, frame) in frames.enumerate() {
+        let frame = frame?;
+        if i % step != 0 {
+            continue;
+        }
+
+        sampled.push(DynamicImage::ImageRgba8(frame.into_buffer()));
+        if sampled.len() > MAX_FRAMES {
+            sampled = sampled.into_iter().step_by(2).collect();
+            step *= 2;
+        }
+    }
+    debug!(
+        "Sampled {} frames from {}, every {} frames",
+        sampled.len(),
+        path,
+        step
+    );
+
+    Ok(sampled)
+}
+
+/// An image with a weight for every pixel, from the mask and the centre weighting.
+#[derive(Debug, Clone)]
+pub struct WeightedImage {
+    pub image: DynamicImage,
+    mask: Option<GrayImage>,
+    center_weight: f64,
+    /// How much the whole image counts compared to others
+    scale: f64,
+}
+
+impl From<DynamicImage> for WeightedImage {
+    fn from(image: DynamicImage) -> Self {
+        Self {
+            image,
+            mask: None,
+            center_weight: 0.0,
+            scale: 1.0,
+        }
+    }
+}
+
+impl WeightedImage {
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Whether some pixels count more than others in the same image.
+    pub fn is_weighted(&self) -> bool {
+        self.mask.is_some() || self.center_weight > 0.0
+    }
+
+    /// The largest weight a pixel can have.
+    pub fn max_weight(&self) -> f64 {
+        (1.0 + self.center_weight) * self.scale
+    }
+
+    pub fn weight(&self, x: u32, y: u32) -> f64 {
+        let mask = self
+            .mask
+            .as_ref()
+            .map_or(1.0, |mask| mask.get_pixel(x, y)[0] as f64 / 255.0)
+            * self.scale;
+
+        if self.center_weight <= 0.0 {
+            return mask;
+        }
+
+        let (width, height) = self.image.dimensions();
+        let dx = (x as f64 + 0.5) / width as f64 - 0.5;
+        let dy = (y as f64 + 0.5) / height as f64 - 0.5;
+        // 0 in the centre and 1 in the corners
+        let distance = (dx * dx + dy * dy).sqrt() / 0.5f64.sqrt();
+
+        mask * (1.0 + self.center_weight * (1.0 - distance))
+    }
+
+    pub fn resize_exact(&self, width: u32, height: u32, filter: FilterType) -> WeightedImage {
+        WeightedImage {
+            image: self.image.resize_exact(width, height, filter),
+            mask: self
+                .mask
+                .as_ref()
+                .map(|mask| image::imageops::resize(mask, width, height, filter)),
+            center_weight: self.center_weight,
+            scale: self.scale,
+        }
+    }
+
+    /// Scales the image down to fit in `width` and `height`, keeping the aspect ratio.
+    pub fn thumbnail(&self, width: u32, height: u32) -> WeightedImage {
+        let image = self.image.thumbnail(width, height);
+        let (width, height) = image.dimensions();
+
+        WeightedImage {
+            mask: self
+                .mask
+                .as_ref()
+                .map(|mask| image::imageops::thumbnail(mask, width, height)),
+            image,
+            center_weight: self.center_weight,
+            scale: self.scale,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_crops() {
+        assert_eq!(
+            "10,0,50%,100%".parse(),
+            Ok(Crop {
+                x: Length::Pixels(10),
+                y: Length::Pixels(0),
+                width: Length::Percent(50.0),
+                height: Length::Percent(100.0),
+            })
+        );
+        assert!("10,0,50%".parse::<Crop>().is_err());
+        assert!("150%".parse::<Length>().is_err());
+    }
+
+    #[test]
+    fn crops_and_removes_margins() {
+        let region = Region {
+            crop: Some("0,0,50%,100%".parse().unwrap()),
+            margin: Some(Length::Pixels(10)),
+            ..Default::default()
+        };
+        assert_eq!(region.bounds(200, 100), (10, 10, 80, 80));
+
+        let region = Region {
+            margin: Some(Length::Percent(10.0)),
+            ..Default::default()
+        };
+        assert_eq!(region.bounds(200, 100), (20, 10, 160, 80));
+    }
+
+    #[test]
+    fn samples_animation_frames() {
+        use image::{codecs::gif::GifEncoder, Frame, Rgba, RgbaImage};
+
+        let path = std::env::temp_dir().join(format!("matugen-frames-{}.gif", std::process::id()));
+        let mut encoder = GifEncoder::new(File::create(&path).unwrap());
+        encoder
+            .encode_frames(
+                (0..4).map(|i| Frame::new(RgbaImage::from_pixel(4, 4, Rgba([i * 60, 0, 0, 255])))),
+            )
+            .unwrap();
+        drop(encoder);
+
+        let frames = decode_frames(path.to_str().unwrap(), 2).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[1].to_rgba8().get_pixel(0, 0)[0], 120);
+    }
+
+    #[test]
+    fn samples_long_animations_evenly() {
+        use image::{codecs::gif::GifEncoder, Frame, Rgba, RgbaImage};
+
+        let path =
+            std::env::temp_dir().join(format!("matugen-long-frames-{}.gif", std::process::id()));
+        let mut encoder = GifEncoder::new(File::create(&path).unwrap());
+        encoder
+            .encode_frames(
+                (0..100).map(|i| Frame::new(RgbaImage::from_pixel(4, 4, Rgba([i * 2, 0, 0, 255])))),
+            )
+            .unwrap();
+        drop(encoder);
+
+        let frames = decode_frames(path.to_str().unwrap(), 1).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        let indices: Vec<u8> = frames
+            .iter()
+            .map(|frame| frame.to_rgba8().get_pixel(0, 0)[0] / 2)
+            .collect();
+        assert!(indices.len() <= MAX_FRAMES);
+        assert_eq!(indices[0], 0);
+        assert!(indices.windows(2).all(|w| w[1] - w[0] == indices[1]));
+        assert!(*indices.last().unwrap() >= 90);
+    }
+
+    #[test]
+    fn weights_the_centre() {
+        let image: WeightedImage = DynamicImage::new_rgb8(11, 11).into();
+        assert!(!image.is_weighted());
+        assert_eq!(image.weight(0, 0), 1.0);
+
+        let image = WeightedImage {
+            center_weight: 3.0,
+            ..image
+        };
+        assert!((image.weight(5, 5) - 4.0).abs() < 0.5);
+        assert!(image.weight(0, 0) < 1.5);
+    }
+}
@@ -0,0 +1,175 @@
+use std::{
+    fs::{self, File},
+    io::{self, Cursor, Read},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use color_eyre::{eyre::WrapErr, Report};
+use image::ImageReader;
+use sha2::{Digest, Sha256};
+
+use crate::util::config::{get_proj_path, ProjectDirsTypes};
+
+/// The path or color that reads from stdin instead.
+pub const STDIN: &str = "-";
+
+/// How many images from stdin are kept in the cache directory, the least recently used ones are
+/// removed.
+const MAX_FILES: usize = 10;
+
+/// Reads an image from stdin and saves it in the cache directory, returning its path.
+///
+/// The file is named after the hash of its contents, so piping the same image again reuses both
+/// the file and the cached colors, and the wallpaper can be set from it like from any other image.
+pub fn read_image() -> Result<PathBuf, Report> {
+    let mut bytes = Vec::new();
+    io::stdin()
+        .read_to_end(&mut bytes)
+        .wrap_err("Could not read the image from stdin")?;
+
+    if bytes.is_empty() {
+        return Err(Report::msg("No image was given on stdin"));
+    }
+
+    let folder = get_proj_path(&ProjectDirsTypes::Cache)
+        .ok_or_else(|| Report::msg("Could not find the cache directory"))?
+        .join("stdin");
+    let path = save_image(&folder, &bytes)?;
+
+    debug!("Saved the image from stdin to {}", path.display());
+
+    Ok(path)
+}
+
+/// The hash of the image followed by the extension of its format, guessed from the contents.
+fn image_file_name(bytes: &[u8]) -> Result<String, Report> {
+    let format = ImageReader::new(Cursor::new(bytes))
+        .with_guessed_format()?
+        .format()
+        .ok_or_else(|| Report::msg("Could not guess the format of the image on stdin"))?;
+
+    Ok(format!(
+        "{:x}.{}",
+        Sha256::digest(bytes),
+        format.extensions_str().first().unwrap_or(&"img")
+    ))
+}
+
+/// Saves the image in `folder`, or marks it as used if it's already there, and removes the old
+/// images.
+fn save_image(folder: &Path, bytes: &[u8]) -> Result<PathBuf, Report> {
+    let path = folder.join(image_file_name(bytes)?);
+
+    if path.exists() {
+        File::options()
+            .append(true)
+            .open(&path)
+            .and_then(|file| file.set_modified(SystemTime::now()))?;
+    } else {
+        fs::create_dir_all(folder)?;
+        fs::write(&path, bytes)
+            .wrap_err(format!("Could not save the image to {}", path.display()))?;
+    }
+
+    remove_old_images(folder, MAX_FILES)?;
+
+    Ok(path)
+}
+
+/// Keeps only the `keep` most recently modified files in `folder`.
+fn remove_old_images(folder: &Path, keep: usize) -> Result<(), Report> {
+    let mut files: Vec<(SystemTime, PathBuf)> = fs::read_dir(folder)?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let modified = entry.metadata().ok()?.modified().ok()?;
+            Some((modified, entry.path()))
+        })
+        .filter(|(_, path)| path.is_file())
+        .collect();
+
+    files.sort_by_key(|(modified, _)| std::cmp::Reverse(*modified));
+
+    for (_, path) in files.into_iter().skip(keep) {
+        debug!("Removing the old image from stdin {}", path.display());
+        fs::remove_file(&path)
+            .wrap_err(format!("Could not remove the old image {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Reads a color string like `#ff0000` from stdin.
+pub fn read_color() -> Result<String, Report> {
+    let mut color = String::new();
+    io::stdin()
+        .read_to_string(&mut color)
+        .wrap_err("Could not read the color from stdin")?;
+
+    let color = color.trim();
+
+    if color.is_empty() {
+        return Err(Report::msg("No color was given on stdin"));
+    }
+
+    Ok(color.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use image::{DynamicImage, ImageFormat};
+
+    use super::*;
+
+    fn encode(format: ImageFormat, red: u8) -> Vec<u8> {
+        let mut bytes = Cursor::new(Vec::new());
+        DynamicImage::ImageRgb8(image::RgbImage::from_pixel(2, 2, image::Rgb([red, 0, 0])))
+            .write_to(&mut bytes, format)
+            .unwrap();
+        bytes.into_inner()
+    }
+
+    #[test]
+    fn names_images_by_their_contents() {
+        let png = encode(ImageFormat::Png, 255);
+
+        let name = image_file_name(&png).unwrap();
+        assert_eq!(name, image_file_name(&png).unwrap());
+        assert_eq!(name, format!("{:x}.png", Sha256::digest(&png)));
+        assert_ne!(name, image_file_name(&encode(ImageFormat::Png, 0)).unwrap());
+
+        assert!(image_file_name(&encode(ImageFormat::Jpeg, 255))
+            .unwrap()
+            .ends_with(".jpg"));
+        assert!(image_file_name(b"not an image").is_err());
+    }
+
+    #[test]
+    fn keeps_the_most_recent_images() {
+        let folder = std::env::temp_dir().join(format!("matugen-stdin-{}", std::process::id()));
+        fs::create_dir_all(&folder).unwrap();
+
+        let now = SystemTime::now();
+        for i in 0..4u64 {
+            let file = File::create(folder.join(format!("{}.png", i))).unwrap();
+            file.set_modified(now - Duration::from_secs(100 - i))
+                .unwrap();
+        }
+
+        let path = save_image(&folder, &encode(ImageFormat::Png, 128)).unwrap();
+        remove_old_images(&folder, 2).unwrap();
+
+        let mut remaining: Vec<PathBuf> = fs::read_dir(&folder)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        remaining.sort();
+        fs::remove_dir_all(&folder).unwrap();
+
+        let mut expected = vec![path, folder.join("3.png")];
+        expected.sort();
+        assert_eq!(remaining, expected);
+    }
+}
@@ -0,0 +1,355 @@
+use colorsys::{Hsl, Rgb};
+use indexmap::IndexMap;
+use material_colors::hct::Hct;
+
+use crate::{
+    color::{format::argb_from_rgb, parse::parse_css_color},
+    expect_args,
+    parser::{value::ColorValue, Engine, FilterError, FilterReturnType, SpannedValue, Value},
+};
+
+const SORT_KEYS: &[&str] = &["hue", "tone", "chroma", "lightness", "name"];
+
+/// Gets a color out of a value, looking into `default` and `color` keys of maps like `colors.primary`.
+fn get_color(value: &Value) -> Option<Rgb> {
+    match value {
+        Value::Color(color) | Value::LazyColor { color, .. } => Some(color.clone()),
+        Value::HslColor(color) => Some(color.into()),
+        Value::Ident(string) => parse_css_color(string).ok(),
+        Value::Map(map) => map
+            .get("color")
+            .or_else(|| map.get("default"))
+            .and_then(get_color),
+        _ => None,
+    }
+}
+
+fn get_sort_value(value: &Value, key: &str) -> Result<f64, FilterError> {
+    let color = get_color(value).ok_or(FilterError::NoColorInValue {
+        value: value.variant_name(),
+    })?;
+
+    Ok(match key {
+        "lightness" => Hsl::from(&color).lightness(),
+        _ => {
+            let hct = Hct::new(argb_from_rgb(&color));
+            match key {
+                "hue" => hct.get_hue(),
+                "tone" => hct.get_tone(),
+                _ => hct.get_chroma(),
+            }
+        }
+    })
+}
+
+/// Matches `*` wildcards, so `surface_*` matches every key starting with `surface_`.
+fn matches_pattern(pattern: &str, string: &str) -> bool {
+    let parts: Vec<&str> = pattern.split('*').collect();
+
+    if parts.len() == 1 {
+        return pattern == string;
+    }
+
+    let (first, last) = (parts[0], parts[parts.len() - 1]);
+    if !string.starts_with(first) || !string[first.len()..].ends_with(last) {
+        return false;
+    }
+
+    let mut rest = &string[first.len()..string.len() - last.len()];
+    for part in &parts[1..parts.len() - 1] {
+        match rest.find(part) {
+            Some(index) => rest = &rest[index + part.len()..],
+            None => return false,
+        }
+    }
+
+    true
+}
+
+/// Turns negative indices into indices from the end, clamped to the length.
+fn resolve_index(index: i64, len: usize) -> usize {
+    if index < 0 {
+        len.saturating_sub(index.unsigned_abs() as usize)
+    } else {
+        (index as usize).min(len)
+    }
+}
+
+fn item_to_string(value: &Value, keywords: &[&str], engine: &Engine) -> String {
+    let format = keywords.last().copied().unwrap_or("hex");
+
+    match value.clone().get_color() {
+        Some(color) => {
+            let color: Rgb = match color {
+                ColorValue::Rgb(color) => color,
+                ColorValue::Hsl(color) => color.into(),
+            };
+            engine
+                .format_color(color.clone(), format)
+                .or_else(|| engine.format_color(color, "hex"))
+                .map(|v| v.to_string())
+                .unwrap_or_default()
+        }
+        None => value.to_string(),
+    }
+}
+
+fn expected_collection(original: &FilterReturnType) -> FilterError {
+    FilterError::ExpectedCollection {
+        actual: original.variant_name(),
+    }
+}
+
+pub(crate) fn length(
+    _keywords: &[&str],
+    _args: &[SpannedValue],
+    original: FilterReturnType,
+    _engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    match original {
+        FilterReturnType::Array(values) => Ok(FilterReturnType::Int(values.len() as i64)),
+        FilterReturnType::Map(map) => Ok(FilterReturnType::Int(map.len() as i64)),
+        FilterReturnType::String(s) => Ok(FilterReturnType::Int(s.chars().count() as i64)),
+        other => Err(expected_collection(&other)),
+    }
+}
+
+pub(crate) fn first(
+    _keywords: &[&str],
+    _args: &[SpannedValue],
+    original: FilterReturnType,
+    _engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    match original {
+        FilterReturnType::Array(values) => {
+            Ok(values.into_iter().next().unwrap_or(Value::Null).into())
+        }
+        FilterReturnType::Map(map) => Ok(map.into_values().next().unwrap_or(Value::Null).into()),
+        other => Err(expected_collection(&other)),
+    }
+}
+
+pub(crate) fn last(
+    _keywords: &[&str],
+    _args: &[SpannedValue],
+    original: FilterReturnType,
+    _engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    match original {
+        FilterReturnType::Array(values) => {
+            Ok(values.into_iter().last().unwrap_or(Value::Null).into())
+        }
+        FilterReturnType::Map(map) => Ok(map.into_values().last().unwrap_or(Value::Null).into()),
+        other => Err(expected_collection(&other)),
+    }
+}
+
+pub(crate) fn keys(
+    _keywords: &[&str],
+    _args: &[SpannedValue],
+    original: FilterReturnType,
+    _engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    match original {
+        FilterReturnType::Map(map) => Ok(FilterReturnType::Array(
+            map.into_keys().map(Value::Ident).collect(),
+        )),
+        other => Err(expected_collection(&other)),
+    }
+}
+
+pub(crate) fn values(
+    _keywords: &[&str],
+    _args: &[SpannedValue],
+    original: FilterReturnType,
+    _engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    match original {
+        FilterReturnType::Map(map) => Ok(FilterReturnType::Array(map.into_values().collect())),
+        FilterReturnType::Array(values) => Ok(FilterReturnType::Array(values)),
+        other => Err(expected_collection(&other)),
+    }
+}
+
+pub(crate) fn reverse(
+    _keywords: &[&str],
+    _args: &[SpannedValue],
+    original: FilterReturnType,
+    _engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    match original {
+        FilterReturnType::Array(values) => {
+            Ok(FilterReturnType::Array(values.into_iter().rev().collect()))
+        }
+        FilterReturnType::Map(map) => Ok(FilterReturnType::Map(map.into_iter().rev().collect())),
+        FilterReturnType::String(s) => Ok(FilterReturnType::String(s.chars().rev().collect())),
+        other => Err(expected_collection(&other)),
+    }
+}
+
+pub(crate) fn slice(
+    _keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    _engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let start = expect_args!(args, i64);
+
+    let end = match args.get(1) {
+        Some(_) => Some(expect_args!(&args[1..], i64)),
+        None => None,
+    };
+
+    let range = |len: usize| {
+        let start = resolve_index(start, len);
+        let end = end.map_or(len, |end| resolve_index(end, len));
+        start..end.max(start)
+    };
+
+    match original {
+        FilterReturnType::Array(values) => {
+            let range = range(values.len());
+            Ok(FilterReturnType::Array(values[range].to_vec()))
+        }
+        FilterReturnType::Map(map) => {
+            let range = range(map.len());
+            Ok(FilterReturnType::Map(
+                map.into_iter()
+                    .skip(range.start)
+                    .take(range.len())
+                    .collect(),
+            ))
+        }
+        FilterReturnType::String(s) => {
+            let range = range(s.chars().count());
+            Ok(FilterReturnType::String(
+                s.chars().skip(range.start).take(range.len()).collect(),
+            ))
+        }
+        other => Err(expected_collection(&other)),
+    }
+}
+
+pub(crate) fn join(
+    keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let separator = expect_args!(args, String);
+
+    let items: Vec<String> = match original {
+        FilterReturnType::Array(values) => values
+            .iter()
+            .map(|v| item_to_string(v, keywords, engine))
+            .collect(),
+        FilterReturnType::Map(map) => map.into_keys().collect(),
+        other => return Err(expected_collection(&other)),
+    };
+
+    Ok(FilterReturnType::String(items.join(&separator)))
+}
+
+pub(crate) fn sort_by(
+    _keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    _engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let key = expect_args!(args, String);
+
+    if !SORT_KEYS.contains(&key.as_str()) {
+        return Err(FilterError::UnexpectedStringValue {
+            expected: SORT_KEYS.join(", "),
+            span: args[0].span,
+        });
+    }
+
+    match original {
+        FilterReturnType::Array(values) => {
+            if key == "name" {
+                let mut values = values;
+                values.sort_by_key(|v| v.to_string());
+                return Ok(FilterReturnType::Array(values));
+            }
+
+            let mut keyed = values
+                .into_iter()
+                .map(|v| Ok((get_sort_value(&v, &key)?, v)))
+                .collect::<Result<Vec<(f64, Value)>, FilterError>>()?;
+            keyed.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+            Ok(FilterReturnType::Array(
+                keyed.into_iter().map(|(_, v)| v).collect(),
+            ))
+        }
+        FilterReturnType::Map(map) => {
+            if key == "name" {
+                let mut map = map;
+                map.sort_keys();
+                return Ok(FilterReturnType::Map(map));
+            }
+
+            let mut keyed = map
+                .into_iter()
+                .map(|(k, v)| Ok((get_sort_value(&v, &key)?, k, v)))
+                .collect::<Result<Vec<(f64, String, Value)>, FilterError>>()?;
+            keyed.sort_by(|(a, ..), (b, ..)| a.total_cmp(b));
+
+            Ok(FilterReturnType::Map(
+                keyed
+                    .into_iter()
+                    .map(|(_, k, v)| (k, v))
+                    .collect::<IndexMap<String, Value>>(),
+            ))
+        }
+        other => Err(expected_collection(&other)),
+    }
+}
+
+pub(crate) fn filter(
+    _keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    _engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let pattern = expect_args!(args, String);
+
+    match original {
+        FilterReturnType::Map(map) => Ok(FilterReturnType::Map(
+            map.into_iter()
+                .filter(|(k, _)| matches_pattern(&pattern, k))
+                .collect(),
+        )),
+        FilterReturnType::Array(values) => Ok(FilterReturnType::Array(
+            values
+                .into_iter()
+                .filter(|v| matches_pattern(&pattern, &v.to_string()))
+                .collect(),
+        )),
+        other => Err(expected_collection(&other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_wildcard_patterns() {
+        assert!(matches_pattern("surface_*", "surface_container"));
+        assert!(matches_pattern("*_container", "primary_container"));
+        assert!(matches_pattern("on_*_container", "on_primary_container"));
+        assert!(matches_pattern("primary", "primary"));
+        assert!(!matches_pattern("primary", "primary_container"));
+        assert!(!matches_pattern("surface_*", "on_surface"));
+    }
+
+    #[test]
+    fn resolves_negative_indices() {
+        assert_eq!(resolve_index(-2, 5), 3);
+        assert_eq!(resolve_index(-10, 5), 0);
+        assert_eq!(resolve_index(2, 5), 2);
+        assert_eq!(resolve_index(10, 5), 5);
+    }
+}
@@ -4,14 +4,12 @@ use material_colors::blend::{harmonize as md3_harmonize, hct_hue};
 
 use crate::{
     color::{
+        cvd::{simulate as simulate_cvd, Deficiency},
         format::{argb_from_hsl, argb_from_rgb, hsl_from_argb, rgb_from_argb},
         parse::parse_css_color,
     },
     expect_args,
-    parser::{
-        engine::{format_color, FORMATS},
-        Engine, FilterError, FilterReturnType, SpannedValue,
-    },
+    parser::{ColorValue, Engine, FilterError, FilterReturnType, SpannedValue},
 };
 
 fn adjust_rgb_lightness(color: &mut Rgb, amount: f64, threshold: f64) {
@@ -38,17 +36,15 @@ pub(crate) fn invert(
     original: FilterReturnType,
     _engine: &Engine,
 ) -> Result<FilterReturnType, FilterError> {
-    match original {
-        FilterReturnType::String(_) => Err(FilterError::ColorFilterOnString),
-        FilterReturnType::Rgb(mut color) => {
+    match original.expect_color()? {
+        ColorValue::Rgb(mut color) => {
             color.invert();
             Ok(FilterReturnType::Rgb(color))
         }
-        FilterReturnType::Hsl(mut color) => {
+        ColorValue::Hsl(mut color) => {
             color.invert();
             Ok(FilterReturnType::Hsl(color))
         }
-        FilterReturnType::Bool(_) => Err(FilterError::ColorFilterOnBool),
     }
 }
 
@@ -58,17 +54,15 @@ pub(crate) fn grayscale(
     original: FilterReturnType,
     _engine: &Engine,
 ) -> Result<FilterReturnType, FilterError> {
-    match original {
-        FilterReturnType::String(_) => Err(FilterError::ColorFilterOnString),
-        FilterReturnType::Rgb(mut color) => {
+    match original.expect_color()? {
+        ColorValue::Rgb(mut color) => {
             color.grayscale_simple();
             Ok(FilterReturnType::Rgb(color))
         }
-        FilterReturnType::Hsl(mut color) => {
+        ColorValue::Hsl(mut color) => {
             color.invert();
             Ok(FilterReturnType::Hsl(color))
         }
-        FilterReturnType::Bool(_) => Err(FilterError::ColorFilterOnBool),
     }
 }
 
@@ -81,17 +75,15 @@ pub(crate) fn lighten(
 ) -> Result<FilterReturnType, FilterError> {
     let amt = expect_args!(args, f64);
 
-    match original {
-        FilterReturnType::String(_) => Err(FilterError::ColorFilterOnString),
-        FilterReturnType::Rgb(mut color) => {
+    match original.expect_color()? {
+        ColorValue::Rgb(mut color) => {
             color.lighten(amt);
             Ok(FilterReturnType::Rgb(color))
         }
-        FilterReturnType::Hsl(mut color) => {
+        ColorValue::Hsl(mut color) => {
             color.grayscale_simple();
             Ok(FilterReturnType::Hsl(color))
         }
-        FilterReturnType::Bool(_) => Err(FilterError::ColorFilterOnBool),
     }
 }
 
@@ -105,17 +97,15 @@ pub(crate) fn auto_lighten(
     let threshold = 50.0;
     let amt = expect_args!(args, f64);
 
-    match original {
-        FilterReturnType::String(_) => Err(FilterError::ColorFilterOnString),
-        FilterReturnType::Rgb(mut color) => {
+    match original.expect_color()? {
+        ColorValue::Rgb(mut color) => {
             adjust_rgb_lightness(&mut color, amt, threshold);
             Ok(FilterReturnType::Rgb(color))
         }
-        FilterReturnType::Hsl(mut color) => {
+        ColorValue::Hsl(mut color) => {
             adjust_hsl_lightness(&mut color, amt, threshold);
             Ok(FilterReturnType::Hsl(color))
         }
-        FilterReturnType::Bool(_) => Err(FilterError::ColorFilterOnBool),
     }
 }
 
@@ -138,17 +128,15 @@ pub(crate) fn saturate(
         }
     };
 
-    match original {
-        FilterReturnType::String(_) => Err(FilterError::ColorFilterOnString),
-        FilterReturnType::Rgb(mut color) => {
+    match original.expect_color()? {
+        ColorValue::Rgb(mut color) => {
             color.saturate(saturation);
             Ok(FilterReturnType::Rgb(color))
         }
-        FilterReturnType::Hsl(mut color) => {
+        ColorValue::Hsl(mut color) => {
             color.saturate(saturation);
             Ok(FilterReturnType::Hsl(color))
         }
-        FilterReturnType::Bool(_) => Err(FilterError::ColorFilterOnBool),
     }
 }
 
@@ -167,10 +155,10 @@ pub(crate) fn to_color(
                 ))
                 .unwrap(),
         )),
-        FilterReturnType::Rgb(color) => Ok(FilterReturnType::Rgb(color)),
-        FilterReturnType::Hsl(color) => Ok(FilterReturnType::Hsl(color)),
-        // TODO: Add proper error here
-        FilterReturnType::Bool(_) => Err(FilterError::ColorFilterOnBool),
+        other => match other.expect_color()? {
+            ColorValue::Rgb(color) => Ok(FilterReturnType::Rgb(color)),
+            ColorValue::Hsl(color) => Ok(FilterReturnType::Hsl(color)),
+        },
     }
 }
 
@@ -178,31 +166,17 @@ pub(crate) fn format(
     _keywords: &[&str],
     args: &[SpannedValue],
     original: FilterReturnType,
-    _engine: &Engine,
+    engine: &Engine,
 ) -> Result<FilterReturnType, FilterError> {
     let format = expect_args!(args, String);
 
-    match original {
-        FilterReturnType::String(_) => Err(FilterError::ColorFilterOnString),
-        FilterReturnType::Rgb(color) => Ok(FilterReturnType::String(
-            format_color(color, &format)
-                .ok_or(FilterError::InvalidFormatString {
-                    expected: FORMATS,
-                    span: args[0].span,
-                })?
-                .to_string(),
-        )),
-        FilterReturnType::Hsl(color) => Ok(FilterReturnType::String(
-            format_color(color.into(), &format)
-                .ok_or(FilterError::InvalidFormatString {
-                    expected: FORMATS,
-                    span: args[0].span,
-                })?
-                .to_string(),
-        )),
-        // TODO: Add proper error here
-        FilterReturnType::Bool(_) => Err(FilterError::ColorFilterOnBool),
-    }
+    Ok(engine
+        .format_color(original.expect_rgb()?, &format)
+        .ok_or_else(|| FilterError::InvalidFormatString {
+            expected: engine.formats(),
+            span: args[0].span,
+        })?
+        .into())
 }
 
 pub(crate) fn blend(
@@ -213,17 +187,15 @@ pub(crate) fn blend(
 ) -> Result<FilterReturnType, FilterError> {
     let (blend_with, amount) = expect_args!(args, Rgb, f64);
 
-    match original {
-        FilterReturnType::String(_) => Err(FilterError::ColorFilterOnString),
-        FilterReturnType::Rgb(color) => {
+    match original.expect_color()? {
+        ColorValue::Rgb(color) => {
             let res = hct_hue(argb_from_rgb(&color), argb_from_rgb(&blend_with), amount);
             Ok(FilterReturnType::Rgb(rgb_from_argb(res)))
         }
-        FilterReturnType::Hsl(color) => {
+        ColorValue::Hsl(color) => {
             let res = hct_hue(argb_from_hsl(&color), argb_from_rgb(&blend_with), amount);
             Ok(FilterReturnType::Hsl(hsl_from_argb(res)))
         }
-        FilterReturnType::Bool(_) => Err(FilterError::ColorFilterOnBool),
     }
 }
 
@@ -235,16 +207,41 @@ pub(crate) fn harmonize(
 ) -> Result<FilterReturnType, FilterError> {
     let blend_with = expect_args!(args, Rgb);
 
-    match original {
-        FilterReturnType::String(_) => Err(FilterError::ColorFilterOnString),
-        FilterReturnType::Rgb(color) => {
+    match original.expect_color()? {
+        ColorValue::Rgb(color) => {
             let res = md3_harmonize(argb_from_rgb(&color), argb_from_rgb(&blend_with));
             Ok(FilterReturnType::Rgb(rgb_from_argb(res)))
         }
-        FilterReturnType::Hsl(color) => {
+        ColorValue::Hsl(color) => {
             let res = md3_harmonize(argb_from_hsl(&color), argb_from_rgb(&blend_with));
             Ok(FilterReturnType::Hsl(hsl_from_argb(res)))
         }
-        FilterReturnType::Bool(_) => Err(FilterError::ColorFilterOnBool),
+    }
+}
+
+pub(crate) fn simulate(
+    _keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    _engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let name = expect_args!(args, String);
+
+    let Some(deficiency) = Deficiency::from_name(&name) else {
+        return Err(FilterError::UnexpectedStringValue {
+            expected: Deficiency::ALL
+                .iter()
+                .map(|d| d.name())
+                .collect::<Vec<_>>()
+                .join(", "),
+            span: args[0].span,
+        });
+    };
+
+    match original.expect_color()? {
+        ColorValue::Rgb(color) => Ok(FilterReturnType::Rgb(simulate_cvd(&color, deficiency))),
+        ColorValue::Hsl(color) => Ok(FilterReturnType::Hsl(
+            simulate_cvd(&color.into(), deficiency).into(),
+        )),
     }
 }
@@ -0,0 +1,188 @@
+use colorsys::{ColorAlpha, Rgb};
+use material_colors::{hct::Hct, utils::math::sanitize_degrees_double};
+use palette::{IntoColor, LinSrgb, Oklab, Srgb};
+
+use crate::{
+    color::format::{argb_from_rgb, rgb_from_argb},
+    expect_args,
+    parser::{Engine, FilterError, FilterReturnType, SpannedValue, Value},
+};
+
+// Upper bound for the number of steps, so a typo can't produce a huge array
+const MAX_STEPS: i64 = 256;
+
+enum GradientSpace {
+    Oklab,
+    Hct,
+    Rgb,
+}
+
+fn lerp(a: f64, b: f64, t: f64) -> f64 {
+    a + (b - a) * t
+}
+
+fn to_oklab(color: &Rgb) -> Oklab {
+    Srgb::new(
+        color.red() as f32 / 255.0,
+        color.green() as f32 / 255.0,
+        color.blue() as f32 / 255.0,
+    )
+    .into_linear()
+    .into_color()
+}
+
+fn from_oklab(color: Oklab) -> Rgb {
+    let linear: LinSrgb = color.into_color();
+    let srgb: Srgb = Srgb::from_linear(linear);
+
+    Rgb::new(
+        (srgb.red.clamp(0.0, 1.0) * 255.0).round() as f64,
+        (srgb.green.clamp(0.0, 1.0) * 255.0).round() as f64,
+        (srgb.blue.clamp(0.0, 1.0) * 255.0).round() as f64,
+        None,
+    )
+}
+
+fn interpolate(start: &Rgb, end: &Rgb, t: f64, space: &GradientSpace) -> Rgb {
+    let mut color = match space {
+        GradientSpace::Rgb => Rgb::new(
+            lerp(start.red(), end.red(), t).round(),
+            lerp(start.green(), end.green(), t).round(),
+            lerp(start.blue(), end.blue(), t).round(),
+            None,
+        ),
+        GradientSpace::Oklab => {
+            let (a, b) = (to_oklab(start), to_oklab(end));
+            let t = t as f32;
+
+            from_oklab(Oklab::new(
+                a.l + (b.l - a.l) * t,
+                a.a + (b.a - a.a) * t,
+                a.b + (b.b - a.b) * t,
+            ))
+        }
+        GradientSpace::Hct => {
+            let a = Hct::new(argb_from_rgb(start));
+            let b = Hct::new(argb_from_rgb(end));
+
+            // Go around the hue circle the shorter way
+            let delta = (b.get_hue() - a.get_hue() + 540.0) % 360.0 - 180.0;
+            let hue = sanitize_degrees_double(a.get_hue() + delta * t);
+
+            rgb_from_argb(
+                Hct::from(
+                    hue,
+                    lerp(a.get_chroma(), b.get_chroma(), t),
+                    lerp(a.get_tone(), b.get_tone(), t),
+                )
+                .into(),
+            )
+        }
+    };
+
+    color.set_alpha(lerp(start.alpha(), end.alpha(), t));
+    color
+}
+
+pub(crate) fn gradient(
+    _keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    _engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let (end, steps) = expect_args!(args, Rgb, i64);
+
+    let space = match args.get(2) {
+        Some(_) => {
+            let space = expect_args!(&args[2..], String);
+            match space.to_lowercase().as_str() {
+                "oklab" => GradientSpace::Oklab,
+                "hct" => GradientSpace::Hct,
+                "rgb" => GradientSpace::Rgb,
+                _ => {
+                    return Err(FilterError::UnexpectedStringValue {
+                        expected: "oklab, hct, rgb".to_owned(),
+                        span: args[2].span,
+                    })
+                }
+            }
+        }
+        None => GradientSpace::Oklab,
+    };
+
+    let start = original.expect_rgb()?;
+
+    if !(2..=MAX_STEPS).contains(&steps) {
+        return Err(FilterError::ArgumentOutOfRange {
+            min: 2,
+            max: MAX_STEPS,
+            actual: steps,
+            span: args[1].span,
+        });
+    }
+
+    let colors = (0..steps)
+        .map(|i| {
+            let t = i as f64 / (steps - 1) as f64;
+            Value::Color(interpolate(&start, &end, t, &space))
+        })
+        .collect();
+
+    Ok(FilterReturnType::Array(colors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_endpoints_in_every_space() {
+        let (start, end) = (
+            Rgb::from_hex_str("#6750a4").unwrap(),
+            Rgb::from_hex_str("#ffd8e4").unwrap(),
+        );
+
+        for space in [GradientSpace::Rgb, GradientSpace::Hct, GradientSpace::Oklab] {
+            assert_eq!(
+                interpolate(&start, &end, 0.0, &space).to_hex_string(),
+                "#6750a4"
+            );
+            assert_eq!(
+                interpolate(&start, &end, 1.0, &space).to_hex_string(),
+                "#ffd8e4"
+            );
+        }
+    }
+
+    #[test]
+    fn interpolates_midpoints() {
+        let (black, white) = (
+            Rgb::from_hex_str("#000000").unwrap(),
+            Rgb::from_hex_str("#ffffff").unwrap(),
+        );
+
+        assert_eq!(
+            interpolate(&black, &white, 0.5, &GradientSpace::Rgb).to_hex_string(),
+            "#808080"
+        );
+        // The middle of Oklab lightness is darker than the sRGB average
+        assert_eq!(
+            interpolate(&black, &white, 0.5, &GradientSpace::Oklab).to_hex_string(),
+            "#636363"
+        );
+    }
+
+    #[test]
+    fn interpolates_alpha() {
+        let mut start = Rgb::from_hex_str("#000000").unwrap();
+        start.set_alpha(0.0);
+
+        let mid = interpolate(
+            &start,
+            &Rgb::from_hex_str("#000000").unwrap(),
+            0.5,
+            &GradientSpace::Hct,
+        );
+        assert_eq!(mid.alpha(), 0.5);
+    }
+}
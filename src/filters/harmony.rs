@@ -0,0 +1,149 @@
+use colorsys::{ColorAlpha, Rgb};
+use material_colors::{hct::Hct, utils::math::sanitize_degrees_double};
+
+use crate::{
+    color::format::{argb_from_rgb, rgb_from_argb},
+    expect_args,
+    parser::{Engine, FilterError, FilterReturnType, SpannedValue, Value},
+};
+
+const HARMONIES: &[&str] = &["complementary", "triadic", "tetradic", "analogous", "split"];
+
+/// Hue offsets of the colors that follow the input color in the harmony.
+fn get_offsets(harmony: &str, angle: f64) -> Option<Vec<f64>> {
+    match harmony {
+        "complementary" => Some(vec![180.0]),
+        "triadic" => Some(vec![120.0, 240.0]),
+        "tetradic" => Some(vec![90.0, 180.0, 270.0]),
+        "analogous" => Some(vec![-angle, angle]),
+        "split" => Some(vec![180.0 - angle, 180.0 + angle]),
+        _ => None,
+    }
+}
+
+fn rotate_hue(color: &Rgb, degrees: f64) -> Rgb {
+    let hct = Hct::new(argb_from_rgb(color));
+
+    let mut rotated = rgb_from_argb(
+        Hct::from(
+            sanitize_degrees_double(hct.get_hue() + degrees),
+            hct.get_chroma(),
+            hct.get_tone(),
+        )
+        .into(),
+    );
+    rotated.set_alpha(color.alpha());
+    rotated
+}
+
+pub(crate) fn harmony(
+    _keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    _engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let harmony = expect_args!(args, String);
+
+    let angle = match args.get(1) {
+        Some(_) => expect_args!(&args[1..], f64),
+        None => 30.0,
+    };
+
+    let Some(offsets) = get_offsets(harmony.as_str(), angle) else {
+        return Err(FilterError::UnexpectedStringValue {
+            expected: HARMONIES.join(", "),
+            span: args[0].span,
+        });
+    };
+
+    let color = original.expect_rgb()?;
+
+    let colors = std::iter::once(Value::Color(color.clone()))
+        .chain(
+            offsets
+                .into_iter()
+                .map(|offset| Value::Color(rotate_hue(&color, offset))),
+        )
+        .collect();
+
+    Ok(FilterReturnType::Array(colors))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(harmony_name: &str, angle: Option<f64>) -> Vec<Rgb> {
+        let mut args = vec![SpannedValue::unspanned(Value::Ident(
+            harmony_name.to_string(),
+        ))];
+        if let Some(angle) = angle {
+            args.push(SpannedValue::unspanned(Value::Float(angle)));
+        }
+
+        let FilterReturnType::Array(values) = harmony(
+            &[],
+            &args,
+            FilterReturnType::Rgb(Rgb::from_hex_str("#6750a4").unwrap()),
+            &Engine::new(),
+        )
+        .unwrap() else {
+            panic!("expected an array");
+        };
+
+        values
+            .into_iter()
+            .map(|value| match value {
+                Value::Color(color) => color,
+                other => panic!("expected a color, got {}", other.variant_name()),
+            })
+            .collect()
+    }
+
+    fn hue(color: &Rgb) -> f64 {
+        Hct::new(argb_from_rgb(color)).get_hue()
+    }
+
+    fn assert_hue_offsets(colors: &[Rgb], offsets: &[f64]) {
+        assert_eq!(colors.len(), offsets.len());
+        assert_eq!(colors[0].to_hex_string(), "#6750a4");
+
+        for (color, offset) in colors.iter().zip(offsets) {
+            let expected = sanitize_degrees_double(hue(&colors[0]) + offset);
+            let diff = (hue(color) - expected + 540.0) % 360.0 - 180.0;
+            assert!(
+                diff.abs() < 1.0,
+                "expected hue {expected}, got {}",
+                hue(color)
+            );
+        }
+    }
+
+    #[test]
+    fn builds_complementary_colors() {
+        assert_hue_offsets(&run("complementary", None), &[0.0, 180.0]);
+    }
+
+    #[test]
+    fn builds_triadic_colors() {
+        assert_hue_offsets(&run("triadic", None), &[0.0, 120.0, 240.0]);
+    }
+
+    #[test]
+    fn builds_analogous_colors() {
+        assert_hue_offsets(&run("analogous", None), &[0.0, -30.0, 30.0]);
+        assert_hue_offsets(&run("analogous", Some(15.0)), &[0.0, -15.0, 15.0]);
+    }
+
+    #[test]
+    fn rejects_unknown_harmonies() {
+        let err = harmony(
+            &[],
+            &[SpannedValue::unspanned(Value::Ident("square".to_string()))],
+            FilterReturnType::Rgb(Rgb::default()),
+            &Engine::new(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, FilterError::UnexpectedStringValue { .. }));
+    }
+}
@@ -0,0 +1,212 @@
+use colorsys::{ColorAlpha, Rgb};
+
+use crate::{
+    color::math::blend_over,
+    expect_args,
+    parser::{Engine, FilterError, FilterReturnType, SpannedValue},
+};
+
+// https://m3.material.io/foundations/interaction/states/state-layers
+const STATE_LAYERS: &[(&str, f64)] = &[
+    ("hover", 0.08),
+    ("focus", 0.10),
+    ("pressed", 0.10),
+    ("dragged", 0.16),
+];
+
+// Surface tint opacity for elevation levels 0 - 5
+const ELEVATION_TINTS: [f64; 6] = [0.0, 0.05, 0.08, 0.11, 0.12, 0.14];
+
+/// Gets the scheme (`dark`, `light` or `default`) out of keywords like `colors.surface.dark.hex`.
+fn get_scheme<'a>(keywords: &[&'a str]) -> &'a str {
+    match keywords.get(2) {
+        Some(scheme) if matches!(*scheme, "dark" | "light" | "default") => scheme,
+        _ => "default",
+    }
+}
+
+fn get_output_mode(args: &[SpannedValue]) -> Result<bool, FilterError> {
+    let mode = expect_args!(args, String);
+
+    match mode.as_str() {
+        "flat" => Ok(true),
+        "rgba" => Ok(false),
+        _ => Err(FilterError::UnexpectedStringValue {
+            expected: "flat, rgba".to_owned(),
+            span: args[0].span,
+        }),
+    }
+}
+
+pub(crate) fn state_layer(
+    _keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    _engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let (content, state) = expect_args!(args, Rgb, String);
+
+    let Some((_, opacity)) = STATE_LAYERS.iter().find(|(name, _)| *name == state) else {
+        return Err(FilterError::UnexpectedStringValue {
+            expected: STATE_LAYERS
+                .iter()
+                .map(|(name, _)| *name)
+                .collect::<Vec<_>>()
+                .join(", "),
+            span: args[1].span,
+        });
+    };
+
+    let flatten = match args.get(2) {
+        Some(_) => get_output_mode(&args[2..])?,
+        None => true,
+    };
+
+    let container = original.expect_rgb()?;
+
+    if flatten {
+        Ok(FilterReturnType::Rgb(blend_over(
+            &content, &container, *opacity,
+        )))
+    } else {
+        let mut layer = content;
+        layer.set_alpha(*opacity);
+        Ok(FilterReturnType::Rgb(layer))
+    }
+}
+
+pub(crate) fn elevation(
+    keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let level = expect_args!(args, i64);
+
+    let Some(opacity) = usize::try_from(level)
+        .ok()
+        .and_then(|level| ELEVATION_TINTS.get(level))
+        .copied()
+    else {
+        return Err(FilterError::ArgumentOutOfRange {
+            min: 0,
+            max: ELEVATION_TINTS.len() as i64 - 1,
+            actual: level,
+            span: args[0].span,
+        });
+    };
+
+    let tint = match args.get(1) {
+        Some(_) => expect_args!(&args[1..], Rgb),
+        None => engine
+            .resolve_color(&["colors", "surface_tint", get_scheme(keywords)])
+            .ok_or(FilterError::MissingContextColor {
+                color: "surface_tint",
+            })?,
+    };
+
+    Ok(FilterReturnType::Rgb(blend_over(
+        &tint,
+        &original.expect_rgb()?,
+        opacity,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{color::format::format_hex, parser::Value};
+
+    fn hex(value: FilterReturnType) -> String {
+        match value {
+            FilterReturnType::Rgb(color) => format_hex(&color),
+            other => panic!("expected a color, got {}", other.variant_name()),
+        }
+    }
+
+    #[test]
+    fn flattens_state_layers() {
+        let engine = Engine::new();
+        let args = [
+            SpannedValue::unspanned(Value::Color(Rgb::from_hex_str("#ffffff").unwrap())),
+            SpannedValue::unspanned(Value::Ident("hover".to_string())),
+        ];
+
+        let hovered = state_layer(
+            &[],
+            &args,
+            FilterReturnType::Rgb(Rgb::from_hex_str("#6750a4").unwrap()),
+            &engine,
+        )
+        .unwrap();
+        assert_eq!(hex(hovered), "#735eab");
+    }
+
+    #[test]
+    fn keeps_state_layers_as_rgba() {
+        let engine = Engine::new();
+        let args = [
+            SpannedValue::unspanned(Value::Color(Rgb::from_hex_str("#ffffff").unwrap())),
+            SpannedValue::unspanned(Value::Ident("dragged".to_string())),
+            SpannedValue::unspanned(Value::Ident("rgba".to_string())),
+        ];
+
+        let FilterReturnType::Rgb(layer) = state_layer(
+            &[],
+            &args,
+            FilterReturnType::Rgb(Rgb::from_hex_str("#6750a4").unwrap()),
+            &engine,
+        )
+        .unwrap() else {
+            panic!("expected a color");
+        };
+        assert_eq!(format_hex(&layer), "#ffffff");
+        assert_eq!(layer.alpha(), 0.16);
+    }
+
+    #[test]
+    fn tints_surfaces_by_elevation() {
+        let engine = Engine::new();
+        let tint = SpannedValue::unspanned(Value::Color(Rgb::from_hex_str("#6750a4").unwrap()));
+
+        let level_0 = elevation(
+            &[],
+            &[SpannedValue::unspanned(Value::Int(0)), tint.clone()],
+            FilterReturnType::Rgb(Rgb::from_hex_str("#fef7ff").unwrap()),
+            &engine,
+        )
+        .unwrap();
+        assert_eq!(hex(level_0), "#fef7ff");
+
+        let level_2 = elevation(
+            &[],
+            &[SpannedValue::unspanned(Value::Int(2)), tint],
+            FilterReturnType::Rgb(Rgb::from_hex_str("#fef7ff").unwrap()),
+            &engine,
+        )
+        .unwrap();
+        assert_eq!(hex(level_2), "#f2eaf8");
+    }
+
+    #[test]
+    fn rejects_elevation_levels_out_of_range() {
+        let engine = Engine::new();
+
+        for level in [-1, 6] {
+            let err = elevation(
+                &[],
+                &[
+                    SpannedValue::unspanned(Value::Int(level)),
+                    SpannedValue::unspanned(Value::Color(Rgb::from_hex_str("#6750a4").unwrap())),
+                ],
+                FilterReturnType::Rgb(Rgb::from_hex_str("#fef7ff").unwrap()),
+                &engine,
+            )
+            .unwrap_err();
+            assert!(matches!(
+                err,
+                FilterError::ArgumentOutOfRange { min: 0, max: 5, actual, .. } if actual == level
+            ));
+        }
+    }
+}
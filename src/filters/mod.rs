@@ -13,3 +13,24 @@ pub(crate) use string::*;
 
 pub mod colortransform;
 pub(crate) use colortransform::*;
+
+pub mod material;
+pub(crate) use material::*;
+
+pub mod gradient;
+pub(crate) use gradient::*;
+
+pub mod harmony;
+pub(crate) use harmony::*;
+
+pub mod palette;
+pub(crate) use palette::*;
+
+pub mod collection;
+pub(crate) use collection::*;
+
+pub mod number;
+pub(crate) use number::*;
+
+pub mod serialize;
+pub(crate) use serialize::*;
@@ -0,0 +1,548 @@
+use crate::{
+    expect_args,
+    parser::{Engine, FilterError, FilterReturnType, SpannedValue, Value},
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Number {
+    Int(i64),
+    Float(f64),
+}
+
+impl Number {
+    fn as_f64(self) -> f64 {
+        match self {
+            Number::Int(i) => i as f64,
+            Number::Float(f) => f,
+        }
+    }
+
+    fn as_i64(self) -> i64 {
+        match self {
+            Number::Int(i) => i,
+            Number::Float(f) => f as i64,
+        }
+    }
+}
+
+impl From<Number> for FilterReturnType {
+    fn from(value: Number) -> Self {
+        match value {
+            Number::Int(i) => FilterReturnType::Int(i),
+            Number::Float(f) => FilterReturnType::Float(f),
+        }
+    }
+}
+
+fn parse_number(string: &str) -> Option<Number> {
+    let string = string.trim();
+    string
+        .parse::<i64>()
+        .map(Number::Int)
+        .or_else(|_| string.parse::<f64>().map(Number::Float))
+        .ok()
+}
+
+/// Gets a number out of the value, colors are formatted using the keyword format so
+/// `colors.primary.default.hue` works like any other number.
+fn expect_number(
+    keywords: &[&str],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<Number, FilterError> {
+    let not_a_number = |actual: &str| FilterError::ExpectedNumber {
+        actual: actual.to_string(),
+    };
+
+    match original {
+        FilterReturnType::Int(i) => Ok(Number::Int(i)),
+        FilterReturnType::Float(f) => Ok(Number::Float(f)),
+        FilterReturnType::String(s) => parse_number(&s).ok_or_else(|| not_a_number(&s)),
+        FilterReturnType::Bool(b) => Ok(Number::Int(b as i64)),
+        FilterReturnType::Rgb(color) => {
+            match engine.format_color(color, keywords.last().copied().unwrap_or("hex")) {
+                Some(Value::Int(i)) => Ok(Number::Int(i)),
+                Some(Value::Float(f)) => Ok(Number::Float(f)),
+                Some(other) => Err(not_a_number(&other.to_string())),
+                None => Err(not_a_number("Color")),
+            }
+        }
+        FilterReturnType::Hsl(color) => {
+            match engine.format_color(color.into(), keywords.last().copied().unwrap_or("hex")) {
+                Some(Value::Int(i)) => Ok(Number::Int(i)),
+                Some(Value::Float(f)) => Ok(Number::Float(f)),
+                Some(other) => Err(not_a_number(&other.to_string())),
+                None => Err(not_a_number("Color")),
+            }
+        }
+        FilterReturnType::Array(_) => Err(FilterError::FilterOnArray),
+        FilterReturnType::Map(_) => Err(FilterError::FilterOnMap),
+    }
+}
+
+// More decimals than an f64 can hold are meaningless
+const MAX_ROUND_DIGITS: i64 = 15;
+
+pub(crate) fn round(
+    keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let digits = match args.first() {
+        Some(_) => expect_args!(args, i64),
+        None => 0,
+    };
+
+    if !(0..=MAX_ROUND_DIGITS).contains(&digits) {
+        return Err(FilterError::ArgumentOutOfRange {
+            min: 0,
+            max: MAX_ROUND_DIGITS,
+            actual: digits,
+            span: args[0].span,
+        });
+    }
+
+    let number = expect_number(keywords, original, engine)?;
+
+    match number {
+        Number::Int(_) => Ok(number.into()),
+        Number::Float(f) if digits == 0 => Ok(FilterReturnType::Int(f.round() as i64)),
+        Number::Float(f) if digits <= 2 => {
+            let factor = 10f64.powi(digits as i32);
+            Ok(FilterReturnType::Float((f * factor).round() / factor))
+        }
+        // Floats are printed with at most 2 decimals, so more are kept by returning a string
+        Number::Float(f) => {
+            let string = format!("{:.*}", digits as usize, f);
+            let string = string.trim_end_matches('0').trim_end_matches('.');
+            Ok(FilterReturnType::String(string.to_string()))
+        }
+    }
+}
+
+pub(crate) fn floor(
+    keywords: &[&str],
+    _args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let number = expect_number(keywords, original, engine)?;
+    Ok(FilterReturnType::Int(number.as_f64().floor() as i64))
+}
+
+pub(crate) fn ceil(
+    keywords: &[&str],
+    _args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let number = expect_number(keywords, original, engine)?;
+    Ok(FilterReturnType::Int(number.as_f64().ceil() as i64))
+}
+
+pub(crate) fn abs(
+    keywords: &[&str],
+    _args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    match expect_number(keywords, original, engine)? {
+        Number::Int(i) => Ok(FilterReturnType::Int(i.abs())),
+        Number::Float(f) => Ok(FilterReturnType::Float(f.abs())),
+    }
+}
+
+pub(crate) fn clamp(
+    keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let (min, max) = expect_args!(args, f64, f64);
+    let (min, max) = (min.min(max), min.max(max));
+
+    match expect_number(keywords, original, engine)? {
+        Number::Int(i) if min.fract() == 0.0 && max.fract() == 0.0 => {
+            Ok(FilterReturnType::Int(i.clamp(min as i64, max as i64)))
+        }
+        number => Ok(FilterReturnType::Float(number.as_f64().clamp(min, max))),
+    }
+}
+
+pub(crate) fn to_int(
+    keywords: &[&str],
+    _args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let number = expect_number(keywords, original, engine)?;
+    Ok(FilterReturnType::Int(number.as_i64()))
+}
+
+pub(crate) fn to_float(
+    keywords: &[&str],
+    _args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let number = expect_number(keywords, original, engine)?;
+    Ok(FilterReturnType::Float(number.as_f64()))
+}
+
+pub(crate) fn printf(
+    keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let format = expect_args!(args, String);
+    let number = expect_number(keywords, original, engine)?;
+
+    format_printf(&format, number)
+        .map(FilterReturnType::String)
+        .ok_or(FilterError::InvalidNumberFormat {
+            format,
+            span: args[0].span,
+        })
+}
+
+pub(crate) fn fmt(
+    keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let format = expect_args!(args, String);
+    let number = expect_number(keywords, original, engine)?;
+
+    format_rust(&format, number)
+        .map(FilterReturnType::String)
+        .ok_or(FilterError::InvalidNumberFormat {
+            format,
+            span: args[0].span,
+        })
+}
+
+#[derive(Debug, Default)]
+struct Spec {
+    left_align: bool,
+    center: bool,
+    zero_pad: bool,
+    plus: bool,
+    space: bool,
+    width: usize,
+    precision: Option<usize>,
+    kind: char,
+}
+
+impl Spec {
+    fn render(&self, number: Number) -> Option<String> {
+        let int = || match number {
+            Number::Int(i) => i,
+            Number::Float(f) => f.round() as i64,
+        };
+
+        let (negative, digits) = match self.kind {
+            'd' | 'i' => {
+                let i = match number {
+                    Number::Int(i) => i,
+                    Number::Float(f) => f as i64,
+                };
+                (i < 0, i.unsigned_abs().to_string())
+            }
+            'f' | 'F' => {
+                let f = number.as_f64();
+                (
+                    f.is_sign_negative() && f != 0.0,
+                    format!("{:.*}", self.precision.unwrap_or(6), f.abs()),
+                )
+            }
+            'e' | 'E' => {
+                let f = number.as_f64();
+                let s = format!("{:.*e}", self.precision.unwrap_or(6), f.abs());
+                let s = if self.kind == 'E' {
+                    s.to_uppercase()
+                } else {
+                    s
+                };
+                (f.is_sign_negative() && f != 0.0, s)
+            }
+            'x' => (int() < 0, format!("{:x}", int().unsigned_abs())),
+            'X' => (int() < 0, format!("{:X}", int().unsigned_abs())),
+            'o' => (int() < 0, format!("{:o}", int().unsigned_abs())),
+            'b' => (int() < 0, format!("{:b}", int().unsigned_abs())),
+            // Plain value, like `{}` or `%s`
+            's' => match number {
+                Number::Int(i) => (i < 0, i.unsigned_abs().to_string()),
+                Number::Float(f) => {
+                    let s = match self.precision {
+                        Some(precision) => format!("{:.*}", precision, f.abs()),
+                        None => f.abs().to_string(),
+                    };
+                    (f.is_sign_negative() && f != 0.0, s)
+                }
+            },
+            _ => return None,
+        };
+
+        let sign = if negative {
+            "-"
+        } else if self.plus {
+            "+"
+        } else if self.space {
+            " "
+        } else {
+            ""
+        };
+
+        let len = sign.len() + digits.len();
+        if len >= self.width {
+            return Some(format!("{}{}", sign, digits));
+        }
+        let missing = self.width - len;
+
+        Some(if self.zero_pad && !self.left_align && !self.center {
+            format!("{}{}{}", sign, "0".repeat(missing), digits)
+        } else if self.left_align {
+            format!("{}{}{}", sign, digits, " ".repeat(missing))
+        } else if self.center {
+            format!(
+                "{}{}{}{}",
+                " ".repeat(missing / 2),
+                sign,
+                digits,
+                " ".repeat(missing - missing / 2)
+            )
+        } else {
+            format!("{}{}{}", " ".repeat(missing), sign, digits)
+        })
+    }
+}
+
+fn parse_usize(chars: &[char], i: &mut usize) -> Option<usize> {
+    let start = *i;
+    while *i < chars.len() && chars[*i].is_ascii_digit() {
+        *i += 1;
+    }
+    if start == *i {
+        return None;
+    }
+    chars[start..*i].iter().collect::<String>().parse().ok()
+}
+
+/// C style formatting, like `%.3f`, `%02x` or `%+d`. Only a single value can be formatted.
+fn format_printf(format: &str, number: Number) -> Option<String> {
+    let chars: Vec<char> = format.chars().collect();
+    let mut out = String::new();
+    let mut found = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        if chars[i] != '%' {
+            out.push(chars[i]);
+            i += 1;
+            continue;
+        }
+        i += 1;
+
+        if chars.get(i) == Some(&'%') {
+            out.push('%');
+            i += 1;
+            continue;
+        }
+
+        let mut spec = Spec::default();
+        while let Some(c) = chars.get(i) {
+            match c {
+                '-' => spec.left_align = true,
+                '0' => spec.zero_pad = true,
+                '+' => spec.plus = true,
+                ' ' => spec.space = true,
+                _ => break,
+            }
+            i += 1;
+        }
+        spec.width = parse_usize(&chars, &mut i).unwrap_or(0);
+        if chars.get(i) == Some(&'.') {
+            i += 1;
+            spec.precision = Some(parse_usize(&chars, &mut i).unwrap_or(0));
+        }
+        spec.kind = *chars.get(i)?;
+        i += 1;
+
+        if found {
+            return None;
+        }
+        found = true;
+        out.push_str(&spec.render(number)?);
+    }
+
+    found.then_some(out)
+}
+
+/// Rust style formatting, like `{:.3}`, `{:02x}` or `{:>8}`. Only a single value can be formatted.
+fn format_rust(format: &str, number: Number) -> Option<String> {
+    let chars: Vec<char> = format.chars().collect();
+    let mut out = String::new();
+    let mut found = false;
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '{' if chars.get(i + 1) == Some(&'{') => {
+                out.push('{');
+                i += 2;
+                continue;
+            }
+            '}' if chars.get(i + 1) == Some(&'}') => {
+                out.push('}');
+                i += 2;
+                continue;
+            }
+            '{' => {}
+            '}' => return None,
+            c => {
+                out.push(c);
+                i += 1;
+                continue;
+            }
+        }
+        i += 1;
+
+        let mut spec = Spec {
+            kind: 's',
+            ..Default::default()
+        };
+
+        if chars.get(i) == Some(&':') {
+            i += 1;
+            match chars.get(i) {
+                Some('<') => spec.left_align = true,
+                Some('^') => spec.center = true,
+                Some('>') => {}
+                _ => i -= 1,
+            }
+            i += 1;
+            if chars.get(i) == Some(&'+') {
+                spec.plus = true;
+                i += 1;
+            }
+            if chars.get(i) == Some(&'0') {
+                spec.zero_pad = true;
+                i += 1;
+            }
+            spec.width = parse_usize(&chars, &mut i).unwrap_or(0);
+            if chars.get(i) == Some(&'.') {
+                i += 1;
+                spec.precision = Some(parse_usize(&chars, &mut i)?);
+            }
+            match chars.get(i) {
+                Some('}') => {}
+                Some(&c) => {
+                    spec.kind = c;
+                    i += 1;
+                }
+                None => return None,
+            }
+        }
+
+        if chars.get(i) != Some(&'}') || found {
+            return None;
+        }
+        i += 1;
+        found = true;
+
+        if spec.kind == 's' && spec.precision.is_some() {
+            spec.kind = 'f';
+        }
+        if spec.kind == 'f' && matches!(number, Number::Int(_)) && spec.precision.is_none() {
+            spec.kind = 'd';
+        }
+        out.push_str(&spec.render(number)?);
+    }
+
+    found.then_some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_to(value: f64, digits: i64) -> Result<FilterReturnType, FilterError> {
+        round(
+            &[],
+            &[SpannedValue::unspanned(Value::Int(digits))],
+            FilterReturnType::Float(value),
+            &Engine::new(),
+        )
+    }
+
+    #[test]
+    fn rounds_to_more_than_two_decimals() {
+        let rendered = |value, digits| round_to(value, digits).unwrap().to_string();
+
+        assert_eq!(rendered(0.125, 2), "0.13");
+        assert_eq!(rendered(0.125, 3), "0.125");
+        assert_eq!(rendered(1.23456, 4), "1.2346");
+        assert_eq!(rendered(2.5, 15), "2.5");
+        assert_eq!(rendered(2.5, 0), "3");
+    }
+
+    #[test]
+    fn rejects_out_of_range_digits() {
+        for digits in [-1, 16, 308] {
+            assert!(matches!(
+                round_to(2.5, digits),
+                Err(FilterError::ArgumentOutOfRange { actual, .. }) if actual == digits
+            ));
+        }
+    }
+
+    #[test]
+    fn formats_printf() {
+        assert_eq!(
+            format_printf("%.3f", Number::Float(0.5)).as_deref(),
+            Some("0.500")
+        );
+        assert_eq!(
+            format_printf("%02x", Number::Int(10)).as_deref(),
+            Some("0a")
+        );
+        assert_eq!(
+            format_printf("%+d%%", Number::Int(5)).as_deref(),
+            Some("+5%")
+        );
+        assert_eq!(
+            format_printf("%5.1f|", Number::Float(-2.26)).as_deref(),
+            Some(" -2.3|")
+        );
+        assert_eq!(format_printf("%d %d", Number::Int(1)), None);
+        assert_eq!(format_printf("no value", Number::Int(1)), None);
+    }
+
+    #[test]
+    fn formats_rust() {
+        assert_eq!(
+            format_rust("{:02x}", Number::Int(255)).as_deref(),
+            Some("ff")
+        );
+        assert_eq!(
+            format_rust("{:.3}", Number::Float(1.0)).as_deref(),
+            Some("1.000")
+        );
+        assert_eq!(
+            format_rust("{:>5}|{{}}", Number::Int(42)).as_deref(),
+            Some("   42|{}")
+        );
+        assert_eq!(
+            format_rust("{:<4}|", Number::Int(7)).as_deref(),
+            Some("7   |")
+        );
+        assert_eq!(
+            format_rust("{}", Number::Float(0.25)).as_deref(),
+            Some("0.25")
+        );
+        assert_eq!(format_rust("{:q}", Number::Int(1)), None);
+    }
+}
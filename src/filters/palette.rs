@@ -0,0 +1,110 @@
+use crate::{
+    color::math::find_closest_color,
+    expect_args,
+    parser::{Engine, FilterError, FilterReturnType, SpannedValue},
+};
+
+pub(crate) fn closest(
+    _keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let name = expect_args!(args, String);
+
+    let return_color = match args.get(1) {
+        Some(_) => {
+            let output = expect_args!(&args[1..], String);
+            match output.as_str() {
+                "name" => false,
+                "color" => true,
+                _ => {
+                    return Err(FilterError::UnexpectedStringValue {
+                        expected: "name, color".to_owned(),
+                        span: args[1].span,
+                    })
+                }
+            }
+        }
+        None => false,
+    };
+
+    let palette = engine
+        .get_palette(&name)
+        .ok_or(FilterError::PaletteNotFound { palette: name })?;
+
+    let color = original.expect_rgb()?;
+
+    match find_closest_color(palette.iter(), &color) {
+        Some(name) if return_color => Ok(FilterReturnType::Rgb(palette[name].clone())),
+        Some(name) => Ok(FilterReturnType::String(name.clone())),
+        None => Ok(FilterReturnType::String(String::new())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use colorsys::Rgb;
+    use indexmap::IndexMap;
+
+    use super::*;
+    use crate::parser::Value;
+
+    fn engine() -> Engine {
+        let mut engine = Engine::new();
+        engine.add_palette(
+            "folders".to_string(),
+            IndexMap::from([
+                ("blue".to_string(), Rgb::from_hex_str("#5294e2").unwrap()),
+                ("green".to_string(), Rgb::from_hex_str("#87b158").unwrap()),
+                ("red".to_string(), Rgb::from_hex_str("#e25252").unwrap()),
+            ]),
+        );
+        engine
+    }
+
+    fn primary() -> FilterReturnType {
+        FilterReturnType::Rgb(Rgb::from_hex_str("#4a7fd0").unwrap())
+    }
+
+    #[test]
+    fn returns_the_closest_name() {
+        let closest = closest(
+            &[],
+            &[SpannedValue::unspanned(Value::from("folders"))],
+            primary(),
+            &engine(),
+        )
+        .unwrap();
+        assert!(matches!(closest, FilterReturnType::String(name) if name == "blue"));
+    }
+
+    #[test]
+    fn returns_the_closest_color() {
+        let closest = closest(
+            &[],
+            &[
+                SpannedValue::unspanned(Value::from("folders")),
+                SpannedValue::unspanned(Value::from("color")),
+            ],
+            primary(),
+            &engine(),
+        )
+        .unwrap();
+        assert!(
+            matches!(closest, FilterReturnType::Rgb(color) if color.to_hex_string() == "#5294e2")
+        );
+    }
+
+    #[test]
+    fn reports_missing_palettes() {
+        let err = closest(
+            &[],
+            &[SpannedValue::unspanned(Value::from("icons"))],
+            primary(),
+            &engine(),
+        )
+        .unwrap_err();
+        assert!(matches!(err, FilterError::PaletteNotFound { palette } if palette == "icons"));
+    }
+}
@@ -0,0 +1,347 @@
+use colorsys::Rgb;
+use indexmap::IndexMap;
+
+use crate::{
+    color::parse::parse_css_color,
+    expect_args,
+    parser::{Engine, FilterError, FilterReturnType, SpannedValue, Value},
+};
+
+/// Colors in the context are stored as maps like `{ "color": "#RRGGBBAA" }`.
+fn as_color(value: &Value) -> Option<Rgb> {
+    match value {
+        Value::Color(color) | Value::LazyColor { color, .. } => Some(color.clone()),
+        Value::HslColor(color) => Some(color.into()),
+        Value::Map(map) if map.len() == 1 => match map.get("color") {
+            Some(Value::Ident(string)) => parse_css_color(string).ok(),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Replaces every color with the value of `format`, so only plain values are left.
+fn format_colors(value: Value, format: &str, engine: &Engine) -> Value {
+    if let Some(color) = as_color(&value) {
+        return engine.format_color(color, format).unwrap_or(Value::Null);
+    }
+
+    match value {
+        Value::Map(map) => Value::Map(
+            map.into_iter()
+                .map(|(k, v)| (k, format_colors(v, format, engine)))
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .map(|v| format_colors(v, format, engine))
+                .collect(),
+        ),
+        other => other,
+    }
+}
+
+/// Gets the format argument at `index`, defaulting to `hex`.
+fn expect_format(
+    args: &[SpannedValue],
+    index: usize,
+    engine: &Engine,
+) -> Result<String, FilterError> {
+    let Some(spanned) = args.get(index) else {
+        return Ok("hex".to_string());
+    };
+
+    let format = expect_args!(&args[index..], String);
+
+    let formats = engine.formats();
+    if !formats.contains(&format) {
+        return Err(FilterError::InvalidFormatString {
+            expected: formats,
+            span: spanned.span,
+        });
+    }
+
+    Ok(format)
+}
+
+fn original_to_value(
+    keywords: &[&str],
+    original: FilterReturnType,
+    format: &str,
+    engine: &Engine,
+) -> Value {
+    let value = match original {
+        // The format after the keyword wins, like `colors.primary.default.rgb | to_json`
+        FilterReturnType::Rgb(color) => {
+            let format = keywords.last().copied().unwrap_or(format);
+            return engine.format_color(color, format).unwrap_or(Value::Null);
+        }
+        FilterReturnType::Hsl(color) => {
+            let format = keywords.last().copied().unwrap_or(format);
+            return engine
+                .format_color(color.into(), format)
+                .unwrap_or(Value::Null);
+        }
+        other => Value::from(other),
+    };
+
+    format_colors(value, format, engine)
+}
+
+fn to_toml_value(value: Value) -> Option<toml::Value> {
+    Some(match value {
+        Value::Map(map) => toml::Value::Table(
+            map.into_iter()
+                .filter_map(|(k, v)| Some((k, to_toml_value(v)?)))
+                .collect(),
+        ),
+        Value::Array(values) => {
+            toml::Value::Array(values.into_iter().filter_map(to_toml_value).collect())
+        }
+        Value::Int(i) => toml::Value::Integer(i),
+        Value::Float(f) => toml::Value::Float(f),
+        Value::Bool(b) => toml::Value::Boolean(b),
+        Value::Null => return None,
+        other => toml::Value::String(other.to_string()),
+    })
+}
+
+fn yaml_key(key: &str) -> String {
+    let plain = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        && !key.starts_with('-');
+
+    if plain {
+        key.to_string()
+    } else {
+        serde_json::Value::from(key).to_string()
+    }
+}
+
+fn is_yaml_block(value: &serde_json::Value) -> bool {
+    match value {
+        serde_json::Value::Object(map) => !map.is_empty(),
+        serde_json::Value::Array(values) => !values.is_empty(),
+        _ => false,
+    }
+}
+
+/// Writes block style YAML, scalars are written like JSON which YAML reads the same way.
+fn write_yaml(value: &serde_json::Value, indent: usize, out: &mut String) {
+    let pad = "  ".repeat(indent);
+
+    match value {
+        serde_json::Value::Object(map) if !map.is_empty() => {
+            for (k, v) in map {
+                if is_yaml_block(v) {
+                    out.push_str(&format!("{}{}:\n", pad, yaml_key(k)));
+                    write_yaml(v, indent + 1, out);
+                } else {
+                    out.push_str(&format!("{}{}: {}\n", pad, yaml_key(k), v));
+                }
+            }
+        }
+        serde_json::Value::Array(values) if !values.is_empty() => {
+            for v in values {
+                if is_yaml_block(v) {
+                    out.push_str(&format!("{}-\n", pad));
+                    write_yaml(v, indent + 1, out);
+                } else {
+                    out.push_str(&format!("{}- {}\n", pad, v));
+                }
+            }
+        }
+        other => out.push_str(&format!("{}{}\n", pad, other)),
+    }
+}
+
+/// Flattens nested maps into `--prefix-parent-child` custom properties. Maps of schemes like
+/// `colors.primary` use their `default` color.
+fn collect_css_vars(
+    value: &Value,
+    name: &str,
+    format: &str,
+    engine: &Engine,
+    vars: &mut IndexMap<String, String>,
+) {
+    if let Some(color) = as_color(value) {
+        if let Some(formatted) = engine.format_color(color, format) {
+            vars.insert(name.to_string(), formatted.to_string());
+        }
+        return;
+    }
+
+    let join = |key: &str| {
+        let key = key.replace('_', "-");
+        if name.is_empty() || name.ends_with('-') {
+            format!("{}{}", name, key)
+        } else {
+            format!("{}-{}", name, key)
+        }
+    };
+
+    match value {
+        Value::Map(map) => match map.get("default") {
+            Some(default) if as_color(default).is_some() => {
+                collect_css_vars(default, name, format, engine, vars)
+            }
+            _ => {
+                for (k, v) in map {
+                    collect_css_vars(v, &join(k), format, engine, vars);
+                }
+            }
+        },
+        Value::Array(values) => {
+            for (i, v) in values.iter().enumerate() {
+                collect_css_vars(v, &join(&i.to_string()), format, engine, vars);
+            }
+        }
+        Value::Null => {}
+        other => {
+            vars.insert(name.to_string(), other.to_string());
+        }
+    }
+}
+
+pub(crate) fn to_json(
+    keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let format = expect_format(args, 0, engine)?;
+    let value = original_to_value(keywords, original, &format, engine);
+
+    let out = serde_json::to_string_pretty(&serde_json::Value::from(&value)).unwrap_or_default();
+
+    Ok(FilterReturnType::String(out))
+}
+
+pub(crate) fn to_toml(
+    keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let format = expect_format(args, 0, engine)?;
+    let value = original_to_value(keywords, original, &format, engine);
+
+    let out = match to_toml_value(value) {
+        Some(toml::Value::Table(table)) => toml::to_string(&table).unwrap_or_default(),
+        Some(other) => other.to_string(),
+        None => String::new(),
+    };
+
+    Ok(FilterReturnType::String(out.trim_end().to_string()))
+}
+
+pub(crate) fn to_yaml(
+    keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let format = expect_format(args, 0, engine)?;
+    let value = original_to_value(keywords, original, &format, engine);
+
+    let mut out = String::new();
+    write_yaml(&serde_json::Value::from(&value), 0, &mut out);
+
+    Ok(FilterReturnType::String(out.trim_end().to_string()))
+}
+
+pub(crate) fn to_css_vars(
+    _keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let format = expect_format(args, 0, engine)?;
+
+    let prefix = match args.get(1) {
+        Some(_) => expect_args!(&args[1..], String),
+        None => "--".to_string(),
+    };
+
+    let selector = match args.get(2) {
+        Some(_) => expect_args!(&args[2..], String),
+        None => ":root".to_string(),
+    };
+
+    let value = match original {
+        map @ (FilterReturnType::Map(_) | FilterReturnType::Array(_)) => Value::from(map),
+        other => {
+            return Err(FilterError::ExpectedCollection {
+                actual: other.variant_name(),
+            })
+        }
+    };
+
+    let mut vars = IndexMap::new();
+    collect_css_vars(&value, &prefix, &format, engine, &mut vars);
+
+    let declarations = vars
+        .iter()
+        .map(|(name, value)| format!("{}: {};", name, value));
+
+    let out = if selector.is_empty() {
+        declarations.collect::<Vec<String>>().join("\n")
+    } else {
+        let body = declarations
+            .map(|line| format!("  {}", line))
+            .collect::<Vec<String>>()
+            .join("\n");
+        format!("{} {{\n{}\n}}", selector, body)
+    };
+
+    Ok(FilterReturnType::String(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(entries: &[(&str, Value)]) -> Value {
+        Value::Map(
+            entries
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.clone()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn writes_json() {
+        let value = map(&[
+            ("name", Value::Ident("a \"b\"".to_string())),
+            ("list", Value::Array(vec![Value::Int(1), Value::Bool(true)])),
+            ("empty", map(&[])),
+        ]);
+
+        let out = to_json(&[], &[], FilterReturnType::from(value), &Engine::new()).unwrap();
+
+        assert_eq!(
+            out.to_string(),
+            "{\n  \"empty\": {},\n  \"list\": [\n    1,\n    true\n  ],\n  \"name\": \"a \\\"b\\\"\"\n}"
+        );
+    }
+
+    #[test]
+    fn writes_yaml() {
+        let value = map(&[
+            (
+                "primary",
+                map(&[("dark", Value::Ident("#fff".to_string()))]),
+            ),
+            ("odd key", Value::Array(vec![Value::Float(0.5)])),
+        ]);
+
+        let mut out = String::new();
+        write_yaml(&serde_json::Value::from(&value), 0, &mut out);
+
+        assert_eq!(out, "\"odd key\":\n  - 0.5\nprimary:\n  dark: \"#fff\"\n");
+    }
+}
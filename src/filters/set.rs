@@ -1,8 +1,8 @@
-use colorsys::{ColorAlpha, Hsl, Rgb};
+use colorsys::{ColorAlpha, Hsl};
 
 use crate::{
     expect_args,
-    parser::{Engine, FilterError, FilterReturnType, SpannedValue},
+    parser::{ColorValue, Engine, FilterError, FilterReturnType, SpannedValue},
 };
 
 pub(crate) fn set_red(
@@ -13,19 +13,9 @@ pub(crate) fn set_red(
 ) -> Result<FilterReturnType, FilterError> {
     let amt = expect_args!(args, f64);
 
-    match original {
-        FilterReturnType::String(_) => Err(FilterError::ColorFilterOnString),
-        FilterReturnType::Rgb(mut color) => {
-            color.set_red(amt);
-            Ok(FilterReturnType::Rgb(color))
-        }
-        FilterReturnType::Hsl(color) => {
-            let mut rgb: Rgb = color.into();
-            rgb.set_red(amt);
-            Ok(FilterReturnType::Rgb(rgb))
-        }
-        FilterReturnType::Bool(_) => Err(FilterError::ColorFilterOnBool),
-    }
+    let mut color = original.expect_rgb()?;
+    color.set_red(amt);
+    Ok(FilterReturnType::Rgb(color))
 }
 
 pub(crate) fn set_green(
@@ -36,19 +26,9 @@ pub(crate) fn set_green(
 ) -> Result<FilterReturnType, FilterError> {
     let amt = expect_args!(args, f64);
 
-    match original {
-        FilterReturnType::String(_) => Err(FilterError::ColorFilterOnString),
-        FilterReturnType::Rgb(mut color) => {
-            color.set_green(amt);
-            Ok(FilterReturnType::Rgb(color))
-        }
-        FilterReturnType::Hsl(color) => {
-            let mut rgb: Rgb = color.into();
-            rgb.set_green(amt);
-            Ok(FilterReturnType::Rgb(rgb))
-        }
-        FilterReturnType::Bool(_) => Err(FilterError::ColorFilterOnBool),
-    }
+    let mut color = original.expect_rgb()?;
+    color.set_green(amt);
+    Ok(FilterReturnType::Rgb(color))
 }
 
 pub(crate) fn set_blue(
@@ -59,19 +39,9 @@ pub(crate) fn set_blue(
 ) -> Result<FilterReturnType, FilterError> {
     let amt = expect_args!(args, f64);
 
-    match original {
-        FilterReturnType::String(_) => Err(FilterError::ColorFilterOnString),
-        FilterReturnType::Rgb(mut color) => {
-            color.set_blue(amt);
-            Ok(FilterReturnType::Rgb(color))
-        }
-        FilterReturnType::Hsl(color) => {
-            let mut rgb: Rgb = color.into();
-            rgb.set_blue(amt);
-            Ok(FilterReturnType::Rgb(rgb))
-        }
-        FilterReturnType::Bool(_) => Err(FilterError::ColorFilterOnBool),
-    }
+    let mut color = original.expect_rgb()?;
+    color.set_blue(amt);
+    Ok(FilterReturnType::Rgb(color))
 }
 
 pub(crate) fn set_alpha(
@@ -82,17 +52,15 @@ pub(crate) fn set_alpha(
 ) -> Result<FilterReturnType, FilterError> {
     let amt = expect_args!(args, f64);
 
-    match original {
-        FilterReturnType::String(_) => Err(FilterError::ColorFilterOnString),
-        FilterReturnType::Rgb(mut color) => {
+    match original.expect_color()? {
+        ColorValue::Rgb(mut color) => {
             color.set_alpha(amt);
             Ok(FilterReturnType::Rgb(color))
         }
-        FilterReturnType::Hsl(mut color) => {
+        ColorValue::Hsl(mut color) => {
             color.set_alpha(amt);
             Ok(FilterReturnType::Hsl(color))
         }
-        FilterReturnType::Bool(_) => Err(FilterError::ColorFilterOnBool),
     }
 }
 
@@ -104,18 +72,16 @@ pub(crate) fn set_hue(
 ) -> Result<FilterReturnType, FilterError> {
     let amt = expect_args!(args, f64);
 
-    match original {
-        FilterReturnType::String(_) => Err(FilterError::ColorFilterOnString),
-        FilterReturnType::Rgb(color) => {
+    match original.expect_color()? {
+        ColorValue::Rgb(color) => {
             let mut hsl: Hsl = color.into();
             hsl.set_hue(amt);
             Ok(FilterReturnType::Hsl(hsl))
         }
-        FilterReturnType::Hsl(mut color) => {
+        ColorValue::Hsl(mut color) => {
             color.set_hue(amt);
             Ok(FilterReturnType::Hsl(color))
         }
-        FilterReturnType::Bool(_) => Err(FilterError::ColorFilterOnBool),
     }
 }
 
@@ -127,18 +93,16 @@ pub(crate) fn set_saturation(
 ) -> Result<FilterReturnType, FilterError> {
     let amt = expect_args!(args, f64);
 
-    match original {
-        FilterReturnType::String(_) => Err(FilterError::ColorFilterOnString),
-        FilterReturnType::Rgb(color) => {
+    match original.expect_color()? {
+        ColorValue::Rgb(color) => {
             let mut hsl: Hsl = color.into();
             hsl.set_saturation(amt);
             Ok(FilterReturnType::Hsl(hsl))
         }
-        FilterReturnType::Hsl(mut color) => {
+        ColorValue::Hsl(mut color) => {
             color.set_saturation(amt);
             Ok(FilterReturnType::Hsl(color))
         }
-        FilterReturnType::Bool(_) => Err(FilterError::ColorFilterOnBool),
     }
 }
 
@@ -150,17 +114,15 @@ pub(crate) fn set_lightness(
 ) -> Result<FilterReturnType, FilterError> {
     let amt = expect_args!(args, f64);
 
-    match original {
-        FilterReturnType::String(_) => Err(FilterError::ColorFilterOnString),
-        FilterReturnType::Rgb(color) => {
+    match original.expect_color()? {
+        ColorValue::Rgb(color) => {
             let mut hsl: Hsl = color.into();
             hsl.set_lightness(amt);
             Ok(FilterReturnType::Hsl(hsl))
         }
-        FilterReturnType::Hsl(mut color) => {
+        ColorValue::Hsl(mut color) => {
             color.set_lightness(amt);
             Ok(FilterReturnType::Hsl(color))
         }
-        FilterReturnType::Bool(_) => Err(FilterError::ColorFilterOnBool),
     }
 }
@@ -1,198 +1,396 @@
 use convert_case::{Case, Casing};
+use regex::Regex;
 
 use crate::{
     expect_args,
-    parser::{engine::format_color, Engine, FilterError, FilterReturnType, SpannedValue},
+    parser::{Engine, FilterError, FilterReturnType, SpannedValue, Value},
 };
 
 pub(crate) fn replace(
     keywords: &[&str],
     args: &[SpannedValue],
     original: FilterReturnType,
-    _engine: &Engine,
+    engine: &Engine,
 ) -> Result<FilterReturnType, FilterError> {
     let (find, replace) = expect_args!(args, String, String);
 
-    match original {
-        FilterReturnType::String(s) => Ok(FilterReturnType::String(s.replace(&find, &replace))),
-        FilterReturnType::Rgb(color) => {
-            let string = format_color(color, keywords.last().expect("Could not get format"));
-            let modified: String = string.unwrap().to_string().replace(&find, &replace);
-            Ok(FilterReturnType::String(modified))
-        }
-        FilterReturnType::Hsl(color) => {
-            let string = format_color(color.into(), keywords.last().expect("Could not get format"));
-            let modified: String = string.unwrap().to_string().replace(&find, &replace);
-            Ok(FilterReturnType::String(modified))
-        }
-        FilterReturnType::Bool(boolean) => match boolean {
-            true => Ok(FilterReturnType::String("true".replace(&find, &replace))),
-            false => Ok(FilterReturnType::String("false".replace(&find, &replace))),
-        },
-    }
+    let string = original.expect_string(keywords, engine)?;
+
+    Ok(FilterReturnType::String(string.replace(&find, &replace)))
 }
 
 pub(crate) fn lower_case(
     keywords: &[&str],
     _args: &[SpannedValue],
     original: FilterReturnType,
-    _engine: &Engine,
+    engine: &Engine,
 ) -> Result<FilterReturnType, FilterError> {
-    match original {
-        FilterReturnType::String(s) => Ok(FilterReturnType::String(s.to_case(Case::Lower))),
-        FilterReturnType::Rgb(color) => {
-            let string =
-                format_color(color, keywords.last().expect("Could not get format")).unwrap();
-            Ok(FilterReturnType::String(
-                string.to_string().to_case(Case::Lower),
-            ))
-        }
-        FilterReturnType::Hsl(color) => {
-            let string =
-                format_color(color.into(), keywords.last().expect("Could not get format")).unwrap();
-            Ok(FilterReturnType::String(
-                string.to_string().to_case(Case::Lower),
-            ))
-        }
-        FilterReturnType::Bool(boolean) => match boolean {
-            true => Ok(FilterReturnType::String(
-                "true".to_string().to_case(Case::Lower),
-            )),
-            false => Ok(FilterReturnType::String(
-                "false".to_string().to_case(Case::Lower),
-            )),
-        },
-    }
+    let string = original.expect_string(keywords, engine)?;
+
+    Ok(FilterReturnType::String(string.to_case(Case::Lower)))
 }
 
 pub(crate) fn camel_case(
     keywords: &[&str],
     _args: &[SpannedValue],
     original: FilterReturnType,
-    _engine: &Engine,
+    engine: &Engine,
 ) -> Result<FilterReturnType, FilterError> {
-    match original {
-        FilterReturnType::String(s) => Ok(FilterReturnType::String(s.to_case(Case::Camel))),
-        FilterReturnType::Rgb(color) => {
-            let string =
-                format_color(color, keywords.last().expect("Could not get format")).unwrap();
-            Ok(FilterReturnType::String(
-                string.to_string().to_case(Case::Camel),
-            ))
-        }
-        FilterReturnType::Hsl(color) => {
-            let string =
-                format_color(color.into(), keywords.last().expect("Could not get format")).unwrap();
-            Ok(FilterReturnType::String(
-                string.to_string().to_case(Case::Camel),
-            ))
-        }
-        FilterReturnType::Bool(boolean) => match boolean {
-            true => Ok(FilterReturnType::String(
-                "true".to_string().to_case(Case::Camel),
-            )),
-            false => Ok(FilterReturnType::String(
-                "false".to_string().to_case(Case::Camel),
-            )),
-        },
-    }
+    let string = original.expect_string(keywords, engine)?;
+
+    Ok(FilterReturnType::String(string.to_case(Case::Camel)))
 }
 
 pub(crate) fn pascal_case(
     keywords: &[&str],
     _args: &[SpannedValue],
     original: FilterReturnType,
-    _engine: &Engine,
+    engine: &Engine,
 ) -> Result<FilterReturnType, FilterError> {
-    match original {
-        FilterReturnType::String(s) => Ok(FilterReturnType::String(s.to_case(Case::Pascal))),
-        FilterReturnType::Rgb(color) => {
-            let string =
-                format_color(color, keywords.last().expect("Could not get format")).unwrap();
-            Ok(FilterReturnType::String(
-                string.to_string().to_case(Case::Pascal),
-            ))
-        }
-        FilterReturnType::Hsl(color) => {
-            let string =
-                format_color(color.into(), keywords.last().expect("Could not get format")).unwrap();
-            Ok(FilterReturnType::String(
-                string.to_string().to_case(Case::Pascal),
-            ))
-        }
-        FilterReturnType::Bool(boolean) => match boolean {
-            true => Ok(FilterReturnType::String(
-                "true".to_string().to_case(Case::Pascal),
-            )),
-            false => Ok(FilterReturnType::String(
-                "false".to_string().to_case(Case::Pascal),
-            )),
-        },
-    }
+    let string = original.expect_string(keywords, engine)?;
+
+    Ok(FilterReturnType::String(string.to_case(Case::Pascal)))
 }
 
 pub(crate) fn snake_case(
     keywords: &[&str],
     _args: &[SpannedValue],
     original: FilterReturnType,
-    _engine: &Engine,
+    engine: &Engine,
 ) -> Result<FilterReturnType, FilterError> {
-    match original {
-        FilterReturnType::String(s) => Ok(FilterReturnType::String(s.to_case(Case::Snake))),
-        FilterReturnType::Rgb(color) => {
-            let string =
-                format_color(color, keywords.last().expect("Could not get format")).unwrap();
-            Ok(FilterReturnType::String(
-                string.to_string().to_case(Case::Snake),
-            ))
-        }
-        FilterReturnType::Hsl(color) => {
-            let string =
-                format_color(color.into(), keywords.last().expect("Could not get format")).unwrap();
+    let string = original.expect_string(keywords, engine)?;
+
+    Ok(FilterReturnType::String(string.to_case(Case::Snake)))
+}
+
+pub(crate) fn kebab_case(
+    keywords: &[&str],
+    _args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let string = original.expect_string(keywords, engine)?;
+
+    Ok(FilterReturnType::String(string.to_case(Case::Kebab)))
+}
+
+pub(crate) fn upper_case(
+    keywords: &[&str],
+    _args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let string = original.expect_string(keywords, engine)?;
+    Ok(FilterReturnType::String(string.to_case(Case::Upper)))
+}
+
+pub(crate) fn title_case(
+    keywords: &[&str],
+    _args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let string = original.expect_string(keywords, engine)?;
+    Ok(FilterReturnType::String(string.to_case(Case::Title)))
+}
+
+pub(crate) fn trim(
+    keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let string = original.expect_string(keywords, engine)?;
+
+    match args.first() {
+        Some(_) => {
+            let chars: Vec<char> = expect_args!(args, String).chars().collect();
             Ok(FilterReturnType::String(
-                string.to_string().to_case(Case::Snake),
+                string.trim_matches(chars.as_slice()).to_string(),
             ))
         }
-        FilterReturnType::Bool(boolean) => match boolean {
-            true => Ok(FilterReturnType::String(
-                "true".to_string().to_case(Case::Snake),
-            )),
-            false => Ok(FilterReturnType::String(
-                "false".to_string().to_case(Case::Snake),
-            )),
-        },
+        None => Ok(FilterReturnType::String(string.trim().to_string())),
     }
 }
 
-pub(crate) fn kebab_case(
+pub(crate) fn truncate(
+    keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let length = expect_args!(args, i64).max(0) as usize;
+
+    let suffix = match args.get(1) {
+        Some(_) => expect_args!(&args[1..], String),
+        None => String::new(),
+    };
+
+    let string = original.expect_string(keywords, engine)?;
+
+    if string.chars().count() <= length {
+        return Ok(FilterReturnType::String(string));
+    }
+
+    let mut truncated: String = string.chars().take(length).collect();
+    truncated.push_str(&suffix);
+
+    Ok(FilterReturnType::String(truncated))
+}
+
+pub(crate) fn pad(
+    keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let width = expect_args!(args, i64).max(0) as usize;
+
+    let side = match args.get(1) {
+        Some(spanned) => {
+            let side = expect_args!(&args[1..], String);
+            if !["left", "right", "center"].contains(&side.as_str()) {
+                return Err(FilterError::UnexpectedStringValue {
+                    expected: "left, right, center".to_string(),
+                    span: spanned.span,
+                });
+            }
+            side
+        }
+        None => "right".to_string(),
+    };
+
+    let fill = match args.get(2) {
+        Some(_) => expect_args!(&args[2..], String)
+            .chars()
+            .next()
+            .unwrap_or(' '),
+        None => ' ',
+    };
+
+    let string = original.expect_string(keywords, engine)?;
+    let missing = width.saturating_sub(string.chars().count());
+
+    let (left, right) = match side.as_str() {
+        "left" => (missing, 0),
+        "center" => (missing / 2, missing - missing / 2),
+        _ => (0, missing),
+    };
+
+    let mut padded = String::with_capacity(string.len() + missing);
+    padded.extend(std::iter::repeat_n(fill, left));
+    padded.push_str(&string);
+    padded.extend(std::iter::repeat_n(fill, right));
+
+    Ok(FilterReturnType::String(padded))
+}
+
+pub(crate) fn split(
+    keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let separator = expect_args!(args, String);
+    let string = original.expect_string(keywords, engine)?;
+
+    Ok(FilterReturnType::Array(
+        string
+            .split(&separator)
+            .map(|part| Value::Ident(part.to_string()))
+            .collect(),
+    ))
+}
+
+pub(crate) fn regex_replace(
+    keywords: &[&str],
+    args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let (pattern, replace) = expect_args!(args, String, String);
+
+    let regex = Regex::new(&pattern).map_err(|e| FilterError::InvalidRegex {
+        error: e.to_string(),
+        span: args[0].span,
+    })?;
+
+    let string = original.expect_string(keywords, engine)?;
+
+    Ok(FilterReturnType::String(
+        regex.replace_all(&string, replace.as_str()).into_owned(),
+    ))
+}
+
+pub(crate) fn escape_json(
     keywords: &[&str],
     _args: &[SpannedValue],
     original: FilterReturnType,
-    _engine: &Engine,
+    engine: &Engine,
 ) -> Result<FilterReturnType, FilterError> {
-    match original {
-        FilterReturnType::String(s) => Ok(FilterReturnType::String(s.to_case(Case::Kebab))),
-        FilterReturnType::Rgb(color) => {
-            let string =
-                format_color(color, keywords.last().expect("Could not get format")).unwrap();
-            Ok(FilterReturnType::String(
-                string.to_string().to_case(Case::Kebab),
-            ))
+    let string = original.expect_string(keywords, engine)?;
+    Ok(FilterReturnType::String(json_escape(&string)))
+}
+
+pub(crate) fn escape_xml(
+    keywords: &[&str],
+    _args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let string = original.expect_string(keywords, engine)?;
+    Ok(FilterReturnType::String(xml_escape(&string)))
+}
+
+pub(crate) fn escape_shell(
+    keywords: &[&str],
+    _args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let string = original.expect_string(keywords, engine)?;
+    Ok(FilterReturnType::String(shell_escape(&string)))
+}
+
+pub(crate) fn escape_css(
+    keywords: &[&str],
+    _args: &[SpannedValue],
+    original: FilterReturnType,
+    engine: &Engine,
+) -> Result<FilterReturnType, FilterError> {
+    let string = original.expect_string(keywords, engine)?;
+    Ok(FilterReturnType::String(css_escape(&string)))
+}
+
+/// Escapes a string so it can be placed between the quotes of a JSON string.
+fn json_escape(string: &str) -> String {
+    let mut out = String::with_capacity(string.len());
+
+    for c in string.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0C}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
         }
-        FilterReturnType::Hsl(color) => {
-            let string =
-                format_color(color.into(), keywords.last().expect("Could not get format")).unwrap();
-            Ok(FilterReturnType::String(
-                string.to_string().to_case(Case::Kebab),
-            ))
+    }
+
+    out
+}
+
+fn xml_escape(string: &str) -> String {
+    let mut out = String::with_capacity(string.len());
+
+    for c in string.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
         }
-        FilterReturnType::Bool(boolean) => match boolean {
-            true => Ok(FilterReturnType::String(
-                "true".to_string().to_case(Case::Kebab),
-            )),
-            false => Ok(FilterReturnType::String(
-                "false".to_string().to_case(Case::Kebab),
-            )),
-        },
+    }
+
+    out
+}
+
+/// Wraps the string in single quotes, which is safe for any POSIX shell.
+fn shell_escape(string: &str) -> String {
+    format!("'{}'", string.replace('\'', "'\\''"))
+}
+
+/// Escapes a string to be used as a css identifier, following `CSS.escape()`.
+fn css_escape(string: &str) -> String {
+    let chars: Vec<char> = string.chars().collect();
+    let mut out = String::with_capacity(string.len());
+
+    for (i, &c) in chars.iter().enumerate() {
+        let code = c as u32;
+        match c {
+            '\0' => out.push('\u{FFFD}'),
+            '\u{01}'..='\u{1F}' | '\u{7F}' => out.push_str(&format!("\\{:x} ", code)),
+            '0'..='9' if i == 0 || (i == 1 && chars[0] == '-') => {
+                out.push_str(&format!("\\{:x} ", code))
+            }
+            '-' if i == 0 && chars.len() == 1 => out.push_str("\\-"),
+            c if code >= 0x80 || c == '-' || c == '_' || c.is_ascii_alphanumeric() => out.push(c),
+            c => {
+                out.push('\\');
+                out.push(c);
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_colors_without_a_keyword_as_hex() {
+        let mut engine = Engine::new();
+        engine.add_filter("to_color", crate::filters::to_color);
+        engine.add_filter("upper_case", upper_case);
+        engine.add_filter("trim", trim);
+        engine.add_filter("replace", replace);
+
+        let mut compile = |source: &str| engine.compile(String::from(source)).ok();
+
+        assert!(compile(r#"{{ "red" | to_color | upper_case }}"#).is_some());
+        assert_eq!(
+            compile(r#"{{ "red" | to_color | trim }}"#),
+            Some(String::from("#ff0000"))
+        );
+        assert_eq!(
+            compile(r##"{{ "red" | to_color | replace: "#", "" }}"##),
+            Some(String::from("ff0000"))
+        );
+    }
+
+    #[test]
+    fn reports_unknown_keyword_formats() {
+        let engine = Engine::new();
+        let color = FilterReturnType::Rgb(colorsys::Rgb::from_hex_str("#ff0000").unwrap());
+
+        let err = color
+            .expect_string(&["colors", "primary", "default", "nope"], &engine)
+            .unwrap_err();
+        assert!(matches!(err, FilterError::UnknownFormat { format, .. } if format == "nope"));
+    }
+
+    #[test]
+    fn escapes_json() {
+        assert_eq!(json_escape("a \"b\"\n\\"), "a \\\"b\\\"\\n\\\\");
+        assert_eq!(json_escape("\u{01}"), "\\u0001");
+    }
+
+    #[test]
+    fn escapes_xml() {
+        assert_eq!(
+            xml_escape("<a href=\"x\">&'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&amp;&apos;&lt;/a&gt;"
+        );
+    }
+
+    #[test]
+    fn escapes_shell() {
+        assert_eq!(shell_escape("it's $HOME"), "'it'\\''s $HOME'");
+    }
+
+    #[test]
+    fn escapes_css() {
+        assert_eq!(css_escape("1st"), "\\31 st");
+        assert_eq!(css_escape("-2x"), "-\\32 x");
+        assert_eq!(css_escape("-"), "\\-");
+        assert_eq!(css_escape("a.b c"), "a\\.b\\ c");
+        assert_eq!(css_escape("surface_container"), "surface_container");
     }
 }
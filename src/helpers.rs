@@ -1,7 +1,7 @@
 use crate::{
     color::{
         base16::{generate_base16_schemes, Backend},
-        color::{get_source_color, Source},
+        color::{get_source_color, ExtractionOptions, Source},
         format::argb_from_rgb,
         parse::parse_css_color,
     },
@@ -78,6 +78,7 @@ pub fn merge_json_source(
 
 pub fn generate_schemes_and_theme(
     args: &Cli,
+    source: &Source,
     config_file: &ConfigFile,
     scheme_type: SchemeTypes,
 ) -> Result<
@@ -93,15 +94,17 @@ pub fn generate_schemes_and_theme(
     let source_color_index = args
         .source_color_index
         .or(config_file.config.source_color_index);
-    let source_color = match &args.source {
+    let extraction = ExtractionOptions::new(args, &config_file.config)?;
+    let source_color = match source {
         Source::Json { path: _ } => None,
         _ => Some(
             (get_source_color(
-                &args.source,
+                source,
                 &args.resize_filter,
                 parsed_fallback_color,
                 &config_file.config.prefer,
                 &source_color_index,
+                &extraction,
             ))
             .wrap_err("Failed to get source color.")?,
         ),
@@ -132,12 +135,13 @@ pub fn generate_schemes_and_theme(
         None => (None, None),
     };
 
-    let base_16 = match &args.source {
+    let base_16 = match source {
         Source::Json { path: _ } => None,
         _ => Some(
             generate_base16_schemes(
-                &args.source,
+                source,
                 args.base16_backend.clone().unwrap_or(Backend::Wal),
+                &extraction.region,
             )
             .wrap_err("Failed to generate base16 color schemes.")?,
         ),
@@ -237,8 +241,9 @@ pub fn set_wallpaper(
     _wallpaper_cfg: &Wallpaper,
     _engine: &mut Engine,
 ) -> Result<(), Report> {
+    // Only one wallpaper can be set, which is the first image
     let path = match &source {
-        Source::Image { path } => path,
+        Source::Image { paths, .. } => &paths[0],
         Source::Color { .. } => return Ok(()),
         #[cfg(feature = "web-image")]
         Source::WebImage { .. } => return Ok(()),
@@ -9,6 +9,9 @@ use material_colors::theme::ThemeBuilder;
 use serde_json::Value;
 
 mod helpers;
+mod plugin;
+#[cfg(feature = "scripting")]
+mod script;
 mod smart_scheme;
 pub mod template;
 mod util;
@@ -18,7 +21,10 @@ use crate::{
     cache::ImageCache,
     color::{
         base16::Backend,
-        color::{get_filter, get_scored_colors_from_image, Source},
+        color::{
+            get_filter, get_scored_colors_from_image, palette_from_definitions, ExtractionOptions,
+            Source, SourceArg,
+        },
     },
     helpers::{
         apply_opacity_to_schemes, generate_schemes_and_theme, get_syntax, json_from_file,
@@ -34,7 +40,11 @@ use template::TemplateFile;
 
 use crate::{
     template::Template,
-    util::{arguments::Cli, color::show_color, config::ConfigFile},
+    util::{
+        arguments::Cli,
+        color::{show_color, show_cvd_report},
+        config::ConfigFile,
+    },
 };
 
 use clap::Parser;
@@ -49,10 +59,13 @@ pub mod template_util;
 
 use crate::{parser::Engine, scheme::Schemes};
 
+use colorsys::Rgb;
 use material_colors::{color::Argb, theme::Theme};
 
 pub struct State {
     pub args: Cli,
+    /// The source of `args`, with a directory resolved into the image picked from it
+    pub source: Source,
     pub config_file: ConfigFile,
     pub config_path: Option<PathBuf>,
     pub source_color: Option<Argb>,
@@ -71,17 +84,29 @@ impl State {
         #[cfg(feature = "jxl-image")]
         jxl_oxide::integration::register_image_decoding_hook();
 
+        // Everything after this only sees the image picked from a directory
+        let source = args.source.clone().resolve()?;
+
         let (mut config_file, config_path) =
             ConfigFile::read(&args).wrap_err("Failed to read config file.")?;
 
         config_file.parse_cli_overrides(&args);
 
+        if let (Some(mask), Some(config_path)) = (&config_file.config.mask, &config_path) {
+            config_file.config.mask = Some(
+                get_absolute_path(config_path, mask)
+                    .wrap_err(format!("Could not find the mask {}", mask.display()))?,
+            );
+        }
+
         let effective_mode = args.mode.unwrap_or(SchemesEnum::Dark);
         let effective_type = args.r#type;
 
         let mut loaded_cache = false;
 
-        let caching_enabled = config_file.config.caching.unwrap_or(false) && args.source.is_image();
+        let caching_enabled = config_file.config.caching.unwrap_or(false) && source.is_image();
+
+        let extraction = ExtractionOptions::new(&args, &config_file.config)?;
 
         let any_template_smart = config_file
             .templates
@@ -93,18 +118,14 @@ impl State {
             || any_template_smart;
 
         let smart_opts: Option<SmartOpts> = if smart_requested {
-            if !args.source.is_image() {
+            if !source.is_image() {
                 warn!(
                     "Smart scheme needs an image source, got <yellow>{:?}</>. Falling back to defaults.",
-                    args.source
+                    source
                 );
                 None
             } else {
-                let image_path = match &args.source {
-                    Source::Image { path } => path,
-                    _ => unreachable!(),
-                };
-                match smart_scheme::get_smart_opts(std::path::Path::new(image_path)) {
+                match smart_scheme::get_smart_opts(&source.images()?, &extraction.region) {
                     Ok(opts) => Some(opts),
                     Err(e) => {
                         warn!(
@@ -141,11 +162,12 @@ impl State {
         };
 
         let image_cache = ImageCache::new(
-            &args.source,
+            &source,
             resolved_type,
             args.contrast.or(config_file.config.contrast),
             args.lightness_dark,
             args.lightness_light,
+            &extraction,
         );
 
         info!(
@@ -153,31 +175,35 @@ impl State {
             default_scheme, resolved_type
         );
 
-        if let Source::Image { path } = &args.source {
-            if args.show_source_colors.is_some_and(|x| x) {
-                let filter = get_filter(&args.resize_filter);
-                let fallback_color = parse_fallback_color(&config_file)?;
-                let ranked = get_scored_colors_from_image(&path, filter, fallback_color)?;
-
-                for color in ranked {
-                    println!("{}", color.to_hex_with_pound());
-                }
-
-                return Ok(Self {
-                    args,
-                    config_file,
-                    config_path,
-                    source_color: None,
-                    theme: None,
-                    schemes: None,
-                    default_scheme,
-                    resolved_type,
-                    smart_variant,
-                    image_hash: image_cache,
-                    loaded_cache,
-                    base16: None,
-                });
+        if source.is_image() && args.show_source_colors.is_some_and(|x| x) {
+            let filter = get_filter(&args.resize_filter);
+            let fallback_color = parse_fallback_color(&config_file)?;
+            let ranked = get_scored_colors_from_image(
+                &source.images()?,
+                filter,
+                fallback_color,
+                &extraction,
+            )?;
+
+            for color in ranked {
+                println!("{}", color.to_hex_with_pound());
             }
+
+            return Ok(Self {
+                args,
+                source,
+                config_file,
+                config_path,
+                source_color: None,
+                theme: None,
+                schemes: None,
+                default_scheme,
+                resolved_type,
+                smart_variant,
+                image_hash: image_cache,
+                loaded_cache,
+                base16: None,
+            });
         }
 
         let (mut schemes, source_color, theme, mut base16) = if caching_enabled {
@@ -198,14 +224,14 @@ impl State {
                             "<d>The cache in <yellow><b>{}</><d> doesn't exist.</>",
                             image_cache.get_path().display()
                         );
-                        generate_schemes_and_theme(&args, &config_file, resolved_type)?
+                        generate_schemes_and_theme(&args, &source, &config_file, resolved_type)?
                     } else {
                         return Err(e.wrap_err("Couldn't load the cache file").suggestion("You may need to regenerate your cache if coming from v3.1.0 and lower."));
                     }
                 }
             }
         } else {
-            generate_schemes_and_theme(&args, &config_file, resolved_type)?
+            generate_schemes_and_theme(&args, &source, &config_file, resolved_type)?
         };
 
         apply_opacity_to_schemes(&mut base16, args.opacity);
@@ -213,6 +239,7 @@ impl State {
 
         Ok(Self {
             args,
+            source,
             config_file,
             config_path,
             source_color,
@@ -243,7 +270,43 @@ impl State {
 
         self.add_engine_filters(&mut engine);
 
-        let mut json = match &self.args.source {
+        if let Some(filters) = &self.config_file.config.filters {
+            for (name, pipeline) in filters {
+                if engine.has_filter(name) {
+                    warn!(
+                        "The custom filter <yellow>{}</> has the same name as a built-in filter and will be ignored.",
+                        name
+                    );
+                    continue;
+                }
+                engine
+                    .add_filter_alias(name, pipeline)
+                    .wrap_err(format!("Invalid custom filter '{}'", name))?;
+            }
+        }
+
+        if let Some(palettes) = &self.config_file.config.palettes {
+            for (name, colors) in palettes {
+                let palette = palette_from_definitions(colors)
+                    .wrap_err(format!("Invalid palette '{}'", name))?;
+                engine.add_palette(name.clone(), palette);
+            }
+        }
+
+        if let Some(formats) = &self.config_file.config.formats {
+            for (name, source) in formats {
+                if crate::parser::engine::FORMATS.contains(&name.as_str()) {
+                    warn!(
+                        "The custom format <yellow>{}</> has the same name as a built-in format and will be ignored.",
+                        name
+                    );
+                    continue;
+                }
+                engine.add_format(name, source.clone());
+            }
+        }
+
+        let mut json = match &self.source {
             Source::Json { path } => json_from_file(&PathBuf::from(path)).unwrap(),
             _ => merge_json_source(
                 json,
@@ -282,8 +345,22 @@ impl State {
             }
         }
 
+        if let Some(scripts) = &self.config_file.config.scripts {
+            #[cfg(feature = "scripting")]
+            script::load_scripts(&mut engine, &mut json, scripts, &self.config_path)?;
+            #[cfg(not(feature = "scripting"))]
+            warn!(
+                "Matugen was built without the <yellow>scripting</> feature, {} scripts will be ignored.",
+                scripts.len()
+            );
+        }
+
+        if let Some(plugins) = &self.config_file.config.plugins {
+            plugin::load_plugins(&mut engine, &mut json, plugins, &self.config_path)?;
+        }
+
         if self.config_file.config.caching.unwrap_or(false)
-            && self.args.source.is_image()
+            && self.source.is_image()
             && !self.loaded_cache
         {
             self.save_cache(&mut json.clone())
@@ -311,17 +388,23 @@ impl State {
     }
 
     pub fn get_render_data(&self) -> Result<serde_json::Value, Report> {
-        let image = match &self.args.source {
-            Source::Image { path } => Some(normalize_path_to_forward_slash(
-                std::fs::canonicalize(path)?
-                    .to_str()
-                    .ok_or_else(|| Report::msg("Could not canonicalize the image path"))?,
-            )),
+        let images = match &self.source {
+            Source::Image { paths, .. } => paths
+                .iter()
+                .map(|path| {
+                    Ok(normalize_path_to_forward_slash(
+                        std::fs::canonicalize(path)?
+                            .to_str()
+                            .ok_or_else(|| Report::msg("Could not canonicalize the image path"))?,
+                    ))
+                })
+                .collect::<Result<Vec<String>, Report>>()?,
             #[cfg(feature = "web-image")]
-            Source::WebImage { .. } => None,
-            Source::Color { .. } => None,
-            Source::Json { path: _ } => None,
+            Source::WebImage { .. } => Vec::new(),
+            Source::Color { .. } => Vec::new(),
+            Source::Json { path: _ } => Vec::new(),
         };
+        let image = images.first();
 
         let is_dark_mode = match self.default_scheme {
             SchemesEnum::Dark => true,
@@ -330,7 +413,7 @@ impl State {
         };
 
         Ok(serde_json::json!({
-            "image": image, "mode": format!("{}", self.default_scheme), "is_dark_mode": is_dark_mode,
+            "image": image, "images": images, "mode": format!("{}", self.default_scheme), "is_dark_mode": is_dark_mode,
         }))
     }
 
@@ -349,21 +432,7 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "#000000" | to_color | set_red: 255 }}</code></pre>
                 /// </md-card>
-                "set_red" => crate::filters::set_red,
-
-                /// <p>Sets the blue channel of a color</p>
-                ///
-                /// <p><strong>Arguments:</strong></p>
-                ///
-                /// <ul>
-                ///     <li><code>Int</code> - the blue channel value</li>
-                /// </ul>
-
-                /// <p><strong>Example:</strong></p>
-                /// <md-card class="code-card">
-                ///     <pre class="code-block"><code class="language-bash">{{ "#000000" | to_color | set_blue: 255 }}</code></pre>
-                /// </md-card>
-                "set_blue" => crate::filters::set_blue,
+                "set_red" (amount: f64) => crate::filters::set_red,
 
                 /// <p>Sets the green channel of a color</p>
                 ///
@@ -377,7 +446,7 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "#000000" | to_color | set_green: 255 }}</code></pre>
                 /// </md-card>
-                "set_green" => crate::filters::set_green,
+                "set_green" (amount: f64) => crate::filters::set_green,
 
                 /// <p>Sets the blue channel of a color</p>
                 ///
@@ -391,7 +460,7 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "#000000" | to_color | set_blue: 255 }}</code></pre>
                 /// </md-card>
-                "set_blue" => crate::filters::set_blue,
+                "set_blue" (amount: f64) => crate::filters::set_blue,
 
                 /// <p>Sets the alpha channel of a color</p>
                 ///
@@ -405,7 +474,7 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "#000000" | to_color | set_alpha: 0.1 }}</code></pre>
                 /// </md-card>
-                "set_alpha" => crate::filters::set_alpha,
+                "set_alpha" (amount: f64) => crate::filters::set_alpha,
 
                 /// <p>Sets the hue channel of a color</p>
                 ///
@@ -419,7 +488,7 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "#000000" | to_color | set_hue: 360 }}</code></pre>
                 /// </md-card>
-                "set_hue" => crate::filters::set_hue,
+                "set_hue" (amount: f64) => crate::filters::set_hue,
 
                 /// <p>Sets the saturation of a color</p>
                 ///
@@ -433,7 +502,7 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "#000000" | to_color | set_saturation: 100.0 }}</code></pre>
                 /// </md-card>
-                "set_saturation" => crate::filters::set_saturation,
+                "set_saturation" (amount: f64) => crate::filters::set_saturation,
 
                 /// <p>Sets the lightness of a color</p>
                 ///
@@ -447,7 +516,7 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "#000000" | to_color | set_lightness: 100 }}</code></pre>
                 /// </md-card>
-                "set_lightness" => crate::filters::set_lightness,
+                "set_lightness" (amount: f64) => crate::filters::set_lightness,
 
                 /// <p>Lightens a color</p>
                 ///
@@ -461,7 +530,7 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "#ffffff" | to_color | lighten: 20.0 }}</code></pre>
                 /// </md-card>
-                "lighten" => crate::filters::lighten,
+                "lighten" (amount: f64) => crate::filters::lighten,
 
                 /// <p>Parses a CSS color string into a color</p>
                 ///
@@ -475,7 +544,7 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "#ff00ff" | to_color }}</code></pre>
                 /// </md-card>
-                "to_color" => crate::filters::to_color,
+                "to_color" () => crate::filters::to_color,
 
                 /// <p>Inverts a color</p>
                 ///
@@ -489,7 +558,7 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "#ffffff" | to_color | invert }}</code></pre>
                 /// </md-card>
-                "invert" => crate::filters::invert,
+                "invert" () => crate::filters::invert,
 
                 /// <p>Converts a color to grayscale</p>
                 ///
@@ -503,7 +572,7 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "#ff0000" | to_color | grayscale }}</code></pre>
                 /// </md-card>
-                "grayscale" => crate::filters::grayscale,
+                "grayscale" () => crate::filters::grayscale,
 
                 /// <p>Automatically lightens or darkens a color based on its current lightness</p>
                 ///
@@ -519,7 +588,7 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "#222222" | to_color | auto_lightness: 10.0 }}</code></pre>
                 /// </md-card>
-                "auto_lightness" => crate::filters::auto_lighten,
+                "auto_lightness" (amount: f64) => crate::filters::auto_lighten,
 
                 /// <p>Adjusts the saturation of a color</p>
                 ///
@@ -527,29 +596,31 @@ impl State {
                 ///
                 /// <ul>
                 ///     <li><code>Float</code> - saturation amount</li>
-                ///     <li><code>String</code> - color space (<code>hsl</code> or <code>hsv</code>)</li>
+                ///     <li><code>String</code> (optional) - color space (<code>hsl</code> or <code>hsv</code>, default <code>hsl</code>)</li>
                 /// </ul>
                 ///
                 /// <p><strong>Example:</strong></p>
                 /// <md-card class="code-card">
-                ///     <pre class="code-block"><code class="language-bash">{{ "#336699" | to_color | saturate: 20.0, "hsl" }}</code></pre>
+                ///     <pre class="code-block"><code class="language-bash">{{ "#336699" | to_color | saturate: 20.0 }}</code></pre>
                 /// </md-card>
-                "saturate" => crate::filters::saturate,
+                "saturate" (amount: f64, space: String = "hsl") => crate::filters::saturate,
 
                 /// <p>Blends two colors together using hue blending</p>
                 ///
+                /// <p>Arguments can also be passed by name, like <code>blend: color={{ colors.tertiary.default.hex }}, amount=0.25</code>.</p>
+                ///
                 /// <p><strong>Arguments:</strong></p>
                 ///
                 /// <ul>
                 ///     <li><code>Color</code> - color to blend with</li>
-                ///     <li><code>Float</code> - blend amount (0.0 - 1.0)</li>
+                ///     <li><code>Float</code> (optional) - blend amount (0.0 - 1.0, default 0.5)</li>
                 /// </ul>
                 ///
                 /// <p><strong>Example:</strong></p>
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "#ff0000" | to_color | blend: {{ "#0000ff" | to_color }}, 0.5 }}</code></pre>
                 /// </md-card>
-                "blend" => crate::filters::blend,
+                "blend" (color: Rgb, amount: f64 = 0.5) => crate::filters::blend,
 
                 /// <p>Harmonizes a color with another using harmonization</p>
                 ///
@@ -563,13 +634,44 @@ impl State {
                 ///
                 /// <p><strong>Example:</strong></p>
                 /// <md-card class="code-card">
-                ///     <pre class="code-block"><code class="language-bash">{{ "#ff0000" | to_color | harmonize: {{ "#00ff00" | to_color }}</code></pre>
+                ///     <pre class="code-block"><code class="language-bash">{{ "#ff0000" | to_color | harmonize: {{ "#00ff00" | to_color }} }}</code></pre>
                 /// </md-card>
-                "harmonize" => crate::filters::harmonize,
+                "harmonize" (color: Rgb) => crate::filters::harmonize,
+
+                /// <p>Simulates how a color looks with a color vision deficiency</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>String</code> - the deficiency (<code>protanopia</code>, <code>deuteranopia</code>, <code>tritanopia</code> or <code>achromatopsia</code>)</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors.error.default.hex | simulate: "deuteranopia" }}</code></pre>
+                /// </md-card>
+                "simulate" (deficiency: String) => crate::filters::simulate,
+
+                /// <p>Finds the closest color in a palette defined in <code>[config.palettes]</code></p>
+                ///
+                /// <p>Colors are compared using their distance in the Lab color space.</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>String</code> - the name of the palette</li>
+                ///     <li><code>String</code> (optional) - <code>name</code> to return the name of the closest color (default), <code>color</code> to return the color itself</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors.primary.default.hex | closest: "folders" }}</code></pre>
+                /// </md-card>
+                "closest" (palette: String, output: String = "name") => crate::filters::closest,
 
                 /// <p>Formats a color into a certain format just like what using the .<format> on a color keyword would. This is useful for colors that are defined in the templates as there is no way to format them otherwise.</p>
                 /// <md-card class="code-card">
-                ///     <pre class="code-block"><code class="language-bash">{{ "#ff00ff" | format: "hex" }}</code></pre>
+                ///     <pre class="code-block"><code class="language-bash">{{ "#ff00ff" | to_color | format: "hex" }}</code></pre>
                 /// </md-card>
                 ///
                 /// <p><strong>Arguments:</strong></p>
@@ -582,7 +684,77 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "#ff00ff" | to_color }}</code></pre>
                 /// </md-card>
-                "format" => crate::filters::format,
+                "format" (format: String) => crate::filters::format,
+
+                /// <p>Applies a Material 3 state layer of a content color over a container color</p>
+                ///
+                /// <p>The content color is drawn at 8% for <code>hover</code>, 10% for <code>focus</code> and <code>pressed</code>, and 16% for <code>dragged</code>.</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>Color</code> - the content color of the state layer</li>
+                ///     <li><code>String</code> - the state (<code>hover</code>, <code>focus</code>, <code>pressed</code> or <code>dragged</code>)</li>
+                ///     <li><code>String</code> (optional) - <code>flat</code> to return an opaque color (default), <code>rgba</code> to return the content color with the state opacity</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors.primary.default.hex | state_layer: {{ colors.on_primary.default.hex }}, "hover" }}</code></pre>
+                /// </md-card>
+                "state_layer" (content: Rgb, state: String, output: String = "flat") => crate::filters::state_layer,
+
+                /// <p>Applies the Material 3 surface tint for an elevation level</p>
+                ///
+                /// <p>The <code>surface_tint</code> color of the same scheme is used unless a tint color is provided.</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>Int</code> - the elevation level (0-5)</li>
+                ///     <li><code>Color</code> (optional) - the tint color</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors.surface.dark.hex | elevation: 2 }}</code></pre>
+                /// </md-card>
+                "elevation" (level: i64, tint: Option<Rgb>) => crate::filters::elevation,
+
+                /// <p>Generates a gradient between two colors, returning an array of colors</p>
+                ///
+                /// <p>Both the start and end color are included in the result. Use it in a <code>for</code> loop to output each step.</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>Color</code> - the end color</li>
+                ///     <li><code>Int</code> - the number of colors to generate (2-256)</li>
+                ///     <li><code>String</code> (optional) - the color space to interpolate in (<code>oklab</code> (default), <code>hct</code> or <code>rgb</code>)</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash"><* for color in colors.primary.default.hex | gradient: {{ colors.tertiary.default.hex }}, 5, "oklab" *>{{ color.hex }} <* endfor *></code></pre>
+                /// </md-card>
+                "gradient" (end: Rgb, steps: i64, space: String = "oklab") => crate::filters::gradient,
+
+                /// <p>Generates a color harmony by rotating the hue in HCT, returning an array of colors</p>
+                ///
+                /// <p>The chroma and tone of the input color are preserved, and the input color is always the first color in the array.</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>String</code> - the harmony (<code>complementary</code>, <code>triadic</code>, <code>tetradic</code>, <code>analogous</code> or <code>split</code>)</li>
+                ///     <li><code>Float</code> (optional) - the angle in degrees used by <code>analogous</code> and <code>split</code> (default 30)</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash"><* for color in colors.primary.default.hex | harmony: "triadic" *>{{ color.hex }} <* endfor *></code></pre>
+                /// </md-card>
+                "harmony" (harmony: String, angle: f64 = 30.0) => crate::filters::harmony,
             },
 
             "String" => {
@@ -598,7 +770,7 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "Hello World" | snake_case }}</code></pre>
                 /// </md-card>
-                "snake_case" => crate::filters::snake_case,
+                "snake_case" () => crate::filters::snake_case,
 
                 /// <p>Converts a value to lowercase</p>
                 ///
@@ -612,7 +784,7 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "Hello World" | lower_case }}</code></pre>
                 /// </md-card>
-                "lower_case" => crate::filters::lower_case,
+                "lower_case" () => crate::filters::lower_case,
 
                 /// <p>Converts a value to camelCase</p>
                 ///
@@ -626,7 +798,7 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "hello world" | camel_case }}</code></pre>
                 /// </md-card>
-                "camel_case" => crate::filters::camel_case,
+                "camel_case" () => crate::filters::camel_case,
 
                 /// <p>Converts a value to PascalCase</p>
                 ///
@@ -640,7 +812,7 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "hello world" | pascal_case }}</code></pre>
                 /// </md-card>
-                "pascal_case" => crate::filters::pascal_case,
+                "pascal_case" () => crate::filters::pascal_case,
 
                 /// <p>Converts a value to kebab-case</p>
                 ///
@@ -654,7 +826,7 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "hello world" | kebab_case }}</code></pre>
                 /// </md-card>
-                "kebab_case" => crate::filters::kebab_case,
+                "kebab_case" () => crate::filters::kebab_case,
 
                 /// <p>Replaces all occurrences of a substring</p>
                 ///
@@ -669,11 +841,536 @@ impl State {
                 /// <md-card class="code-card">
                 ///     <pre class="code-block"><code class="language-bash">{{ "hello world" | replace: "world", "there" }}</code></pre>
                 /// </md-card>
-                "replace" => crate::filters::replace,
+                "replace" (find: String, replace: String) => crate::filters::replace,
+
+                /// <p>Converts a value to UPPERCASE</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li>None</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ "Hello World" | upper_case }}</code></pre>
+                /// </md-card>
+                "upper_case" () => crate::filters::upper_case,
+
+                /// <p>Converts a value to Title Case</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li>None</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ "hello_world" | title_case }}</code></pre>
+                /// </md-card>
+                "title_case" () => crate::filters::title_case,
+
+                /// <p>Removes whitespace, or the given characters, from both ends of a value</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>String</code> (optional) - the characters to remove</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ "--hello--" | trim: "-" }}</code></pre>
+                /// </md-card>
+                "trim" (chars: Option<String>) => crate::filters::trim,
+
+                /// <p>Shortens a value to a maximum number of characters</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>Int</code> - the maximum length</li>
+                ///     <li><code>String</code> (optional) - text appended when the value was shortened</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ "hello world" | truncate: 5, "…" }}</code></pre>
+                /// </md-card>
+                "truncate" (length: i64, suffix: String = "") => crate::filters::truncate,
+
+                /// <p>Pads a value with a character until it is the given width</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>Int</code> - the width</li>
+                ///     <li><code>String</code> (optional) - the side to pad (<code>left</code>, <code>right</code> or <code>center</code>, default <code>right</code>)</li>
+                ///     <li><code>String</code> (optional) - the character to pad with (default a space)</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ "primary" | pad: 12, "left", "." }}</code></pre>
+                /// </md-card>
+                "pad" (width: i64, side: String = "right", fill: String = " ") => crate::filters::pad,
+
+                /// <p>Splits a value into an array of strings</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>String</code> - the separator</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ "a/b/c" | split: "/" | last }}</code></pre>
+                /// </md-card>
+                "split" (separator: String) => crate::filters::split,
+
+                /// <p>Replaces all matches of a regular expression</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>String</code> - the regular expression</li>
+                ///     <li><code>String</code> - replacement text, capture groups can be used with <code>$1</code></li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ "hello world" | regex_replace: "(\\w+) (\\w+)", "$2 $1" }}</code></pre>
+                /// </md-card>
+                "regex_replace" (pattern: String, replacement: String) => crate::filters::regex_replace,
+
+                /// <p>Escapes a value so it can be used inside of a JSON string</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li>None</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">"{{ image | escape_json }}"</code></pre>
+                /// </md-card>
+                "escape_json" () => crate::filters::escape_json,
+
+                /// <p>Escapes the special characters of XML and HTML</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li>None</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ "a & b" | escape_xml }}</code></pre>
+                /// </md-card>
+                "escape_xml" () => crate::filters::escape_xml,
+
+                /// <p>Quotes a value so it is passed as a single argument in a POSIX shell</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li>None</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">notify-send {{ image | escape_shell }}</code></pre>
+                /// </md-card>
+                "escape_shell" () => crate::filters::escape_shell,
+
+                /// <p>Escapes a value so it can be used as a CSS identifier</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li>None</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">.{{ "1st-theme" | escape_css }} {}</code></pre>
+                /// </md-card>
+                "escape_css" () => crate::filters::escape_css,
+            },
+
+            "Numbers" => {
+                /// <p>Rounds a number to the nearest integer, or to the given number of decimals</p>
+                ///
+                /// <p>Numbers are printed with at most 2 decimals, so with more decimals the result is a string. Use <code>printf</code> or <code>fmt</code> for a fixed number of decimals.</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>Int</code> (optional) - the number of decimals, 0-15 (default 0)</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors.primary.default.alpha | round: 1 }}</code></pre>
+                /// </md-card>
+                "round" (digits: i64 = 0) => crate::filters::round,
+
+                /// <p>Rounds a number down</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li>None</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ 2.7 | floor }}</code></pre>
+                /// </md-card>
+                "floor" () => crate::filters::floor,
+
+                /// <p>Rounds a number up</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li>None</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ 2.2 | ceil }}</code></pre>
+                /// </md-card>
+                "ceil" () => crate::filters::ceil,
+
+                /// <p>Returns the absolute value of a number</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li>None</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ -5 | abs }}</code></pre>
+                /// </md-card>
+                "abs" () => crate::filters::abs,
+
+                /// <p>Limits a number to a range</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>Float</code> - the minimum</li>
+                ///     <li><code>Float</code> - the maximum</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors.primary.default.saturation | clamp: 20, 80 }}</code></pre>
+                /// </md-card>
+                "clamp" (min: f64, max: f64) => crate::filters::clamp,
+
+                /// <p>Converts a value to an integer, dropping any decimals</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li>None</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ "42" | to_int }}</code></pre>
+                /// </md-card>
+                "to_int" () => crate::filters::to_int,
+
+                /// <p>Converts a value to a float</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li>None</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors.primary.default.red | to_float }}</code></pre>
+                /// </md-card>
+                "to_float" () => crate::filters::to_float,
+
+                /// <p>Formats a number using a C style format string</p>
+                ///
+                /// <p>Supports the <code>-</code>, <code>0</code>, <code>+</code> and space flags, a width and a precision. Use <code>%%</code> for a literal percent sign.</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>String</code> - the format, with a single conversion like <code>%d</code>, <code>%.3f</code>, <code>%02x</code> or <code>%e</code></li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors.primary.default.red | to_float | printf: "%.3f" }}</code></pre>
+                /// </md-card>
+                "printf" (format: String) => crate::filters::printf,
+
+                /// <p>Formats a number using a Rust style format string</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>String</code> - the format, with a single placeholder like <code>{}</code>, <code>{:.3}</code>, <code>{:02x}</code> or <code>{:>8}</code></li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors.primary.default.red | fmt: "{:02x}" }}</code></pre>
+                /// </md-card>
+                "fmt" (format: String) => crate::filters::fmt,
+            },
+
+            "Collections" => {
+                /// <p>Returns the number of items in an array or map, or the number of characters in a string</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li>None</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors | length }}</code></pre>
+                /// </md-card>
+                "length" () => crate::filters::length,
+
+                /// <p>Returns the first item of an array, or the first value of a map</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li>None</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors.primary.default.hex | harmony: "triadic" | first | to_color }}</code></pre>
+                /// </md-card>
+                "first" () => crate::filters::first,
+
+                /// <p>Returns the last item of an array, or the last value of a map</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li>None</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors.primary.default.hex | harmony: "triadic" | last | to_color }}</code></pre>
+                /// </md-card>
+                "last" () => crate::filters::last,
+
+                /// <p>Returns the keys of a map as an array</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li>None</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors | keys | join: ", " }}</code></pre>
+                /// </md-card>
+                "keys" () => crate::filters::keys,
+
+                /// <p>Returns the values of a map as an array</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li>None</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash"><* for value in colors | values *>{{ value.default.hex }} <* endfor *></code></pre>
+                /// </md-card>
+                "values" () => crate::filters::values,
+
+                /// <p>Reverses the order of an array, map or string</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li>None</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors.primary.default.hex | gradient: {{ colors.tertiary.default.hex }}, 5 | reverse | join: " " }}</code></pre>
+                /// </md-card>
+                "reverse" () => crate::filters::reverse,
+
+                /// <p>Returns a part of an array, map or string</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>Int</code> - the start index, negative values count from the end</li>
+                ///     <li><code>Int</code> (optional) - the end index (exclusive), negative values count from the end</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors | keys | slice: 0, 3 | join: ", " }}</code></pre>
+                /// </md-card>
+                "slice" (start: i64, end: Option<i64>) => crate::filters::slice,
+
+                /// <p>Joins the items of an array, or the keys of a map, into a string</p>
+                ///
+                /// <p>Colors inside of the array are formatted using the format after the keyword, like <code>.rgb</code>, and <code>hex</code> otherwise.</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>String</code> - the separator</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors.primary.default.hex | gradient: {{ colors.tertiary.default.hex }}, 5 | join: ", " }}</code></pre>
+                /// </md-card>
+                "join" (separator: String) => crate::filters::join,
+
+                /// <p>Sorts an array or map by a color property</p>
+                ///
+                /// <p>Map values like <code>colors.primary</code> are sorted by their <code>default</code> color.</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>String</code> - what to sort by (<code>hue</code>, <code>tone</code>, <code>chroma</code>, <code>lightness</code> or <code>name</code>)</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash"><* for name, value in colors | sort_by: "tone" *>{{ name }}: {{ value.default.hex }}<* endfor *></code></pre>
+                /// </md-card>
+                "sort_by" (key: String) => crate::filters::sort_by,
+
+                /// <p>Keeps only the map keys, or array items, matching a pattern</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>String</code> - the pattern, where <code>*</code> matches any text</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash"><* for name, value in colors | filter: "surface_*" *>{{ name }}: {{ value.default.hex }}<* endfor *></code></pre>
+                /// </md-card>
+                "filter" (pattern: String) => crate::filters::filter,
+
+                /// <p>Serializes a value into JSON</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>String</code> (optional) - the format used for colors (default <code>hex</code>)</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors | to_json: "hex" }}</code></pre>
+                /// </md-card>
+                "to_json" (format: String = "hex") => crate::filters::to_json,
+
+                /// <p>Serializes a value into TOML</p>
+                ///
+                /// <p>Maps are written as tables, so this works best on maps like <code>colors</code>.</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>String</code> (optional) - the format used for colors (default <code>hex</code>)</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors | to_toml: "rgb" }}</code></pre>
+                /// </md-card>
+                "to_toml" (format: String = "hex") => crate::filters::to_toml,
+
+                /// <p>Serializes a value into YAML</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>String</code> (optional) - the format used for colors (default <code>hex</code>)</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors | to_yaml: "hex" }}</code></pre>
+                /// </md-card>
+                "to_yaml" (format: String = "hex") => crate::filters::to_yaml,
+
+                /// <p>Turns a map into a block of CSS custom properties</p>
+                ///
+                /// <p>Nested keys are joined with <code>-</code> and underscores become dashes, colors like <code>colors.primary</code> use the <code>default</code> scheme.</p>
+                ///
+                /// <p><strong>Arguments:</strong></p>
+                ///
+                /// <ul>
+                ///     <li><code>String</code> (optional) - the format used for colors (default <code>hex</code>)</li>
+                ///     <li><code>String</code> (optional) - the prefix of the property names (default <code>--</code>)</li>
+                ///     <li><code>String</code> (optional) - the selector of the block (default <code>:root</code>), an empty string only outputs the properties</li>
+                /// </ul>
+                ///
+                /// <p><strong>Example:</strong></p>
+                /// <md-card class="code-card">
+                ///     <pre class="code-block"><code class="language-bash">{{ colors | to_css_vars: "hex", "--md-" }}</code></pre>
+                /// </md-card>
+                "to_css_vars" (format: String = "hex", prefix: String = "--", selector: String = ":root") => crate::filters::to_css_vars,
             },
         });
     }
 
+    /// Renders the examples of the built-in filters, on an engine without the aliases, formats,
+    /// palettes, scripts and plugins of the config so the result is the same for everyone.
+    #[cfg(feature = "filter-docs")]
+    fn check_filter_docs(&self) -> Result<(), Report> {
+        let mut engine = Engine::new();
+        self.add_engine_filters(&mut engine);
+        engine.add_context(merge_json_source(
+            self.get_render_data()?,
+            &self.schemes,
+            &self.base16,
+            &self.theme,
+            self.default_scheme,
+        )?);
+
+        let problems = crate::parser::filters::docs::check_filter_docs(&mut engine);
+        if problems > 0 {
+            return Err(Report::msg(format!(
+                "Found {} problems in the filter documentation",
+                problems
+            )));
+        }
+
+        Ok(())
+    }
+
     fn init_in_term(&self) -> Result<(), Report> {
         #[cfg(feature = "update-informer")]
         if self.config_file.config.version_check == Some(true) {
@@ -687,7 +1384,7 @@ impl State {
     pub fn run_in_term(&self) -> Result<(), Report> {
         self.init_in_term()?;
 
-        if self.args.show_colors == Some(true) && !self.args.source.is_json() {
+        if self.args.show_colors == Some(true) && !self.source.is_json() {
             show_color(
                 self.schemes.as_ref(),
                 self.source_color.as_ref(),
@@ -695,6 +1392,17 @@ impl State {
             );
         }
 
+        if self.args.check_cvd == Some(true) {
+            if let Some(schemes) = self.schemes.as_ref() {
+                show_cvd_report(schemes);
+            }
+        }
+
+        #[cfg(feature = "filter-docs")]
+        if self.args.check_filter_docs == Some(true) {
+            return self.check_filter_docs();
+        }
+
         let (mut engine, mut json_value) = self
             .init_engine()
             .wrap_err("Something went wrong while initializing the engine")?;
@@ -709,6 +1417,21 @@ impl State {
                     return Ok(());
                 }
             }
+
+            if let Some(format) = &self.args.filter_docs {
+                use crate::{
+                    parser::{filters::docs, helpers::filters_to_html},
+                    util::arguments::FilterDocsFormat,
+                };
+                let docs = match format {
+                    FilterDocsFormat::Html => filters_to_html(),
+                    FilterDocsFormat::Markdown => docs::filters_to_markdown(),
+                    FilterDocsFormat::Json => docs::filters_to_json(),
+                    FilterDocsFormat::Man => docs::filters_to_man(),
+                };
+                println!("{}", docs);
+                return Ok(());
+            }
         }
 
         #[cfg(feature = "dump-json")]
@@ -717,6 +1440,7 @@ impl State {
             if !self.args.include_image_in_json.unwrap_or(true) {
                 if let Some(obj) = json_value.as_object_mut() {
                     obj.remove("image");
+                    obj.remove("images");
                 };
             };
             dump_json(&mut json_value, format, self.args.old_json_output);
@@ -731,7 +1455,7 @@ impl State {
 
         if let Some(_wallpaper_cfg) = &self.config_file.config.wallpaper {
             if _wallpaper_cfg.set.unwrap_or(true) {
-                set_wallpaper(&self.args.source, _wallpaper_cfg, &mut engine)?;
+                set_wallpaper(&self.source, _wallpaper_cfg, &mut engine)?;
             }
         }
 
@@ -763,9 +1487,11 @@ fn main() -> Result<(), Report> {
 
     #[allow(unused_variables)]
     let default_args = Cli {
-        source: crate::Source::Color(crate::color::color::ColorFormat::Hex {
-            string: String::from("#ffffff"),
-        }),
+        source: SourceArg::Source(crate::Source::Color(
+            crate::color::color::ColorFormat::Hex {
+                string: String::from("#ffffff"),
+            },
+        )),
         r#type: SchemeTypes::SchemeContent,
         config: None,
         prefix: None,
@@ -788,10 +1514,23 @@ fn main() -> Result<(), Report> {
         base16_backend: Some(Backend::Wal),
         #[cfg(feature = "filter-docs")]
         filter_docs_html: Some(false),
+        #[cfg(feature = "filter-docs")]
+        filter_docs: None,
+        #[cfg(feature = "filter-docs")]
+        check_filter_docs: Some(false),
         lightness_dark: Some(0.0),
         lightness_light: Some(0.0),
         source_color_index: None,
+        quantizer: None,
+        crop: None,
+        margin: None,
+        center_weight: None,
+        mask: None,
+        alpha_mode: None,
+        alpha_background: None,
+        frame_step: None,
         show_source_colors: None,
+        check_cvd: None,
         opacity: Some(1.0),
     };
 
@@ -2,9 +2,12 @@ use std::{cell::RefCell, collections::HashMap};
 
 use ariadne::{Color, Label, Report, ReportKind, Source};
 use chumsky::{error::Rich, prelude::*, span::SimpleSpan};
+use colorsys::Rgb;
+use indexmap::IndexMap;
 
 use crate::parser::{
-    context::RuntimeContext, filtertype::FilterFn, Error, ErrorCollector, SpannedValue,
+    context::RuntimeContext, filtertype::BoxedFilterFn, helpers::FilterParam, Error,
+    ErrorCollector, FilterError, FilterReturnType, SpannedValue,
 };
 
 use super::context::Context;
@@ -24,6 +27,10 @@ enum Expression {
         name: SimpleSpan,
         args: Vec<Box<SpannedExpr>>,
     },
+    NamedArg {
+        name: SimpleSpan,
+        value: Box<SpannedExpr>,
+    },
     KeywordWithFilters {
         keyword: Box<SpannedExpr>,
         filters: Vec<SpannedExpr>,
@@ -112,13 +119,17 @@ pub struct SpannedExpr {
 }
 
 pub struct Engine {
-    filters: HashMap<&'static str, FilterFn>,
+    filters: HashMap<String, BoxedFilterFn>,
+    filter_params: HashMap<String, Vec<FilterParam>>,
     syntax: EngineSyntax,
     context: Context,
     runtime: RefCell<RuntimeContext>,
     templates: HashMap<String, Template>,
     sources: Vec<String>,
     errors: ErrorCollector,
+    palettes: HashMap<String, IndexMap<String, Rgb>>,
+    /// The custom formats and filter aliases that are being rendered, to catch recursion.
+    expanding: RefCell<Vec<String>>,
 }
 
 pub struct Template {
@@ -163,18 +174,21 @@ impl EngineSyntax {
 
 impl Engine {
     pub fn new() -> Self {
-        let filters: HashMap<&str, FilterFn> = HashMap::new();
+        let filters: HashMap<String, BoxedFilterFn> = HashMap::new();
 
         let ctx = Context::new();
 
         Self {
             filters,
+            filter_params: HashMap::new(),
             syntax: EngineSyntax::default(),
             context: ctx.clone(),
             runtime: RuntimeContext::new(ctx.clone()).into(),
             templates: HashMap::new(),
             sources: vec![],
             errors: ErrorCollector::new(),
+            palettes: HashMap::new(),
+            expanding: RefCell::new(Vec::new()),
         }
     }
 
@@ -182,14 +196,116 @@ impl Engine {
         std::mem::replace(&mut self.syntax, syntax)
     }
 
-    pub fn add_filter(&mut self, name: &'static str, function: FilterFn) -> Option<FilterFn> {
-        self.filters.insert(name, function)
+    /// Adds a filter, which can be a plain [`FilterFn`](crate::parser::FilterFn) or a closure
+    /// capturing its own state.
+    pub fn add_filter<F>(&mut self, name: impl Into<String>, function: F) -> Option<BoxedFilterFn>
+    where
+        F: Fn(
+                &[&str],
+                &[SpannedValue],
+                FilterReturnType,
+                &Engine,
+            ) -> Result<FilterReturnType, FilterError>
+            + Send
+            + Sync
+            + 'static,
+    {
+        self.filters.insert(name.into(), Box::new(function))
     }
-    pub fn remove_filter(&mut self, name: &'static str) -> Option<FilterFn> {
+    pub fn remove_filter(&mut self, name: &str) -> Option<BoxedFilterFn> {
+        self.filter_params.remove(name);
         self.filters.remove(name)
     }
+    pub fn has_filter(&self, name: &str) -> bool {
+        self.filters.contains_key(name)
+    }
+    /// Declares the parameters of a filter, enabling named arguments and defaults for it.
+    pub fn set_filter_params(
+        &mut self,
+        name: impl Into<String>,
+        params: Vec<FilterParam>,
+    ) -> Option<Vec<FilterParam>> {
+        self.filter_params.insert(name.into(), params)
+    }
+    pub fn get_filter_params(&self, name: &str) -> Option<&[FilterParam]> {
+        self.filter_params.get(name).map(|params| params.as_slice())
+    }
+
+    pub fn add_palette(
+        &mut self,
+        name: String,
+        colors: IndexMap<String, Rgb>,
+    ) -> Option<IndexMap<String, Rgb>> {
+        self.palettes.insert(name, colors)
+    }
+
+    pub fn get_palette(&self, name: &str) -> Option<&IndexMap<String, Rgb>> {
+        self.palettes.get(name)
+    }
+
+    /// Adds a custom color format, the source is rendered with the channels of the color in scope.
+    pub fn add_format(&mut self, name: &str, source: String) {
+        self.add_template(format!("format:{}", name), source);
+    }
+
+    /// Adds a filter that runs a chain of other filters, like `set_alpha: 0.6 | lighten: $1`.
+    /// `$1`, `$2`, ... are replaced with the arguments the alias is called with.
+    pub fn add_filter_alias(&mut self, name: &str, pipeline: &str) -> Result<(), FilterError> {
+        let (pipeline, arg_count) = substitute_alias_args(pipeline, &self.syntax);
+        let template_name = format!("filter:{}", name);
+
+        let added = self.try_add_template(
+            template_name.clone(),
+            format!(
+                "{} alias_value | {} {}",
+                self.syntax.keyword_left, pipeline, self.syntax.keyword_right
+            ),
+        );
+        // Only a single `alias_value | ...` expression can be applied to the filtered value
+        let is_pipeline = added
+            && matches!(
+                self.templates[&template_name].ast.as_slice(),
+                [expr] if matches!(expr.expr, Expression::KeywordWithFilters { .. })
+            );
+
+        if !is_pipeline {
+            self.remove_template(&template_name);
+            return Err(FilterError::InvalidAlias {
+                filter: name.to_string(),
+            });
+        }
+
+        self.add_filter(
+            name,
+            move |keywords: &[&str],
+                  args: &[SpannedValue],
+                  original: FilterReturnType,
+                  engine: &Engine| {
+                if args.len() < arg_count {
+                    return Err(FilterError::NotEnoughArguments);
+                }
+                if let Some(extra) = args.get(arg_count) {
+                    return Err(FilterError::TooManyArguments {
+                        expected: arg_count,
+                        span: extra.span,
+                    });
+                }
+                engine.apply_filter_alias(&template_name, keywords, args, original)
+            },
+        );
+
+        Ok(())
+    }
 
     pub fn add_template(&mut self, name: String, source: String) {
+        if !self.try_add_template(name, source) {
+            std::process::exit(1);
+        }
+    }
+
+    /// Like `add_template`, but returns `false` instead of exiting when the source can't be
+    /// parsed. The parse errors are still shown.
+    pub fn try_add_template(&mut self, name: String, source: String) -> bool {
         self.sources.push(source);
         let source_id = self.sources.len() - 1;
         let source_ref = &self.sources[source_id];
@@ -198,17 +314,21 @@ impl Engine {
 
         let (ast, errs) = parser.parse(source_ref).into_output_errors();
 
+        let Some(ast) = ast else {
+            self.show_errors(errs, source_ref);
+            return false;
+        };
+
         self.templates.insert(
             name.clone(),
             Template {
                 name,
                 source_id,
-                ast: ast.unwrap_or_else(|| {
-                    self.show_errors(errs, source_ref);
-                    std::process::exit(1);
-                }),
+                ast,
             },
         );
+
+        true
     }
 
     pub fn remove_template(&mut self, name: &String) -> bool {
@@ -281,3 +401,258 @@ impl Engine {
         });
     }
 }
+
+/// Replaces `$1`, `$2`, ... outside of strings with the variables the arguments of an alias are
+/// stored in, returning the highest argument used. `$$` is a literal `$`.
+fn substitute_alias_args(pipeline: &str, syntax: &EngineSyntax) -> (String, usize) {
+    let mut out = String::with_capacity(pipeline.len());
+    let mut chars = pipeline.chars().peekable();
+    let mut in_string = false;
+    let mut arg_count = 0;
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if in_string => {
+                out.push(c);
+                if let Some(next) = chars.next() {
+                    out.push(next);
+                }
+            }
+            '"' => {
+                in_string = !in_string;
+                out.push(c);
+            }
+            '$' if !in_string && chars.peek() == Some(&'$') => {
+                chars.next();
+                out.push('$');
+            }
+            '$' if !in_string && chars.peek().is_some_and(|c| c.is_ascii_digit()) => {
+                let mut digits = String::new();
+                while let Some(digit) = chars.next_if(|c| c.is_ascii_digit()) {
+                    digits.push(digit);
+                }
+                let index: usize = digits.parse().unwrap_or(0);
+                arg_count = arg_count.max(index);
+                out.push_str(&format!(
+                    "{} alias_arg_{} {}",
+                    syntax.keyword_left, index, syntax.keyword_right
+                ));
+            }
+            c => out.push(c),
+        }
+    }
+
+    (out, arg_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{KeywordError, ParseErrorKind};
+
+    fn format_engine() -> Engine {
+        let mut engine = Engine::new();
+        engine.add_filter("to_color", crate::filters::to_color);
+        engine.add_filter("format", crate::filters::format);
+        engine.add_context(serde_json::json!({
+            "colors": { "primary": { "default": { "color": "#ff8000" } } }
+        }));
+        engine
+    }
+
+    #[test]
+    fn custom_formats_have_the_channels_in_scope() {
+        let mut engine = format_engine();
+        engine.add_format(
+            "vars",
+            String::from(
+                "{{ red }} {{ green }} {{ blue }} {{ alpha }} {{ red_f }} {{ green_f }} {{ blue_f }} {{ hue }} {{ saturation }} {{ lightness }}",
+            ),
+        );
+
+        assert_eq!(
+            engine
+                .compile(String::from("{{ colors.primary.default.vars }}"))
+                .ok(),
+            Some(String::from("255 128 0 1 1 0.5 0 30 100 50"))
+        );
+        assert_eq!(
+            engine
+                .compile(String::from(
+                    r##"{{ "#000000" | to_color | format: "vars" }}"##
+                ))
+                .ok(),
+            Some(String::from("0 0 0 1 0 0 0 0 0 0"))
+        );
+    }
+
+    #[test]
+    fn custom_formats_are_part_of_colors() {
+        let mut engine = format_engine();
+        engine.add_format("qml", String::from("Qt.rgba({{ red_f }})"));
+
+        let res = engine
+            .compile(String::from(
+                "<* for name, value in colors.primary.default.hex | to_color *><* if {{ loop.last }} *>{{ name }}={{ value }}<* endif *><* endfor *>",
+            ))
+            .ok();
+        assert_eq!(res, Some(String::from("qml=Qt.rgba(1)")));
+        assert!(engine.formats().contains(&String::from("qml")));
+    }
+
+    #[test]
+    fn recursive_custom_formats_are_an_error() {
+        let mut engine = format_engine();
+        engine.add_format(
+            "loop",
+            String::from(r##"{{ "#ffffff" | to_color | format: "loop" }}"##),
+        );
+
+        let errors = engine
+            .compile(String::from("{{ colors.primary.default.loop }}"))
+            .unwrap_err();
+        assert!(errors.iter().any(|error| matches!(
+            error,
+            Error::ParseError {
+                kind: ParseErrorKind::Keyword(KeywordError::RecursiveFormat { format }),
+                ..
+            } if format == "loop"
+        )));
+    }
+
+    #[test]
+    fn does_arithmetic_on_filter_results() {
+        let mut engine = Engine::new();
+        engine.add_filter("length", crate::filters::length);
+        engine.add_context(serde_json::json!({
+            "items": ["a", "b", "c"],
+            "ratio": 0.5,
+        }));
+
+        let mut compile = |source: &str| engine.compile(String::from(source)).ok();
+
+        assert_eq!(
+            compile("{{ {{ items | length }} + 1 }}"),
+            Some(String::from("4"))
+        );
+        assert_eq!(
+            compile("{{ {{ items | length }} * {{ ratio }} }}"),
+            Some(String::from("1.5"))
+        );
+        assert_eq!(compile("{{ {{ ratio }} + 1 }}"), Some(String::from("1.5")));
+    }
+
+    #[test]
+    fn closure_filters_can_capture_state() {
+        let mut engine = Engine::new();
+        let lookup: HashMap<String, String> =
+            HashMap::from([("primary".to_string(), "accent".to_string())]);
+
+        engine.add_filter(
+            String::from("lookup"),
+            move |_: &[&str], _: &[SpannedValue], original: FilterReturnType, _: &Engine| {
+                let key = original.to_string();
+                Ok(FilterReturnType::String(
+                    lookup.get(&key).cloned().unwrap_or(key),
+                ))
+            },
+        );
+
+        assert_eq!(
+            engine
+                .compile(String::from(r#"{{ "primary" | lookup }}"#))
+                .ok(),
+            Some(String::from("accent"))
+        );
+    }
+
+    fn filter_errors(errors: Vec<Error>) -> Vec<FilterError> {
+        errors
+            .into_iter()
+            .filter_map(|error| match error {
+                Error::ParseError {
+                    kind: ParseErrorKind::Filter(e),
+                    ..
+                } => Some(e),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn substitutes_alias_args() {
+        let syntax = EngineSyntax::default();
+
+        assert_eq!(
+            substitute_alias_args("lighten: $1 | set_alpha: $2", &syntax),
+            (
+                String::from("lighten: {{ alias_arg_1 }} | set_alpha: {{ alias_arg_2 }}"),
+                2
+            )
+        );
+        assert_eq!(
+            substitute_alias_args(r#"replace: "$1", "$$" | format: $$"#, &syntax),
+            (String::from(r#"replace: "$1", "$$" | format: $"#), 0)
+        );
+        assert_eq!(
+            substitute_alias_args(r#"replace: "\"$1", $3"#, &syntax),
+            (String::from(r#"replace: "\"$1", {{ alias_arg_3 }}"#), 3)
+        );
+    }
+
+    #[test]
+    fn filter_aliases_check_their_arguments() {
+        let mut engine = Engine::new();
+        engine.add_filter("replace", crate::filters::replace);
+        engine.add_filter_alias("swap", "replace: $1, $2").unwrap();
+
+        assert_eq!(
+            engine
+                .compile(String::from(r#"{{ "a-b" | swap: "-", "_" }}"#))
+                .ok(),
+            Some(String::from("a_b"))
+        );
+
+        let errors = engine
+            .compile(String::from(r#"{{ "a-b" | swap: "-" }}"#))
+            .unwrap_err();
+        assert!(matches!(
+            filter_errors(errors).as_slice(),
+            [FilterError::NotEnoughArguments]
+        ));
+
+        let errors = engine
+            .compile(String::from(r#"{{ "a-b" | swap: "-", "_", "+" }}"#))
+            .unwrap_err();
+        assert!(matches!(
+            filter_errors(errors).as_slice(),
+            [FilterError::TooManyArguments { expected: 2, .. }]
+        ));
+    }
+
+    #[test]
+    fn recursive_filter_aliases_are_an_error() {
+        let mut engine = Engine::new();
+        engine.add_filter_alias("selfref", "selfref").unwrap();
+        engine.add_filter_alias("ping", "pong").unwrap();
+        engine.add_filter_alias("pong", "ping").unwrap();
+
+        for source in [r#"{{ "a" | selfref }}"#, r#"{{ "a" | ping }}"#] {
+            let errors = engine.compile(String::from(source)).unwrap_err();
+            assert!(filter_errors(errors)
+                .iter()
+                .any(|error| matches!(error, FilterError::RecursiveAlias { .. })));
+        }
+    }
+
+    #[test]
+    fn rejects_aliases_that_are_not_a_pipeline() {
+        let mut engine = Engine::new();
+
+        assert!(matches!(
+            engine.add_filter_alias("text", "upper_case }} text {{ colors"),
+            Err(FilterError::InvalidAlias { filter }) if filter == "text"
+        ));
+        assert!(!engine.has_filter("text"));
+    }
+}
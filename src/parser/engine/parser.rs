@@ -116,16 +116,27 @@ impl Engine {
                     })
                 });
 
+            let filter_arg = literal.clone().or(arg.clone()).or(expr.clone());
+
+            let named_arg = text::ident()
+                .map_with(|_, e| e.span())
+                .then_ignore(just('=').padded())
+                .then(filter_arg.clone())
+                .map_with(|(name, value), e| {
+                    Box::new(SpannedExpr {
+                        expr: Expression::NamedArg { name, value },
+                        span: e.span(),
+                    })
+                });
+
             let filter = text::ident()
                 .map_with(|_, e| e.span())
                 .then(
                     just(':')
                         .padded()
                         .ignore_then(
-                            literal
-                                .clone()
-                                .or(arg.clone())
-                                .or(expr.clone())
+                            named_arg
+                                .or(filter_arg)
                                 .padded()
                                 .separated_by(just(',').padded())
                                 .collect::<Vec<Box<SpannedExpr>>>(),
@@ -153,7 +164,7 @@ impl Engine {
                 .or(arg)
                 .or(expr.clone())
                 .padded()
-                .then(filters)
+                .then(filters.clone())
                 .map(|(access, filters)| {
                     let keyword = SpannedExpr {
                         span: access.span.clone(),
@@ -274,6 +285,23 @@ impl Engine {
                     })
                 });
 
+            let iterable = dotted_ident.then(filters).map_with(|(access, filters), e| {
+                if filters.is_empty() {
+                    access
+                } else {
+                    Box::new(SpannedExpr {
+                        expr: Expression::KeywordWithFilters {
+                            keyword: Box::new(SpannedExpr {
+                                span: access.span,
+                                expr: Expression::Keyword { keywords: access },
+                            }),
+                            filters,
+                        },
+                        span: e.span(),
+                    })
+                }
+            });
+
             let for_loop = just("for")
                 .padded()
                 .ignore_then(
@@ -284,7 +312,7 @@ impl Engine {
                 )
                 .padded()
                 .then_ignore(just("in").padded())
-                .then(dotted_ident.or(range).padded())
+                .then(iterable.or(range).padded())
                 .then_ignore(just(syntax.block_right.as_str()))
                 .then(expr.repeated().collect())
                 .delimited_by(
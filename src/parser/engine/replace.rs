@@ -3,17 +3,20 @@ use colorsys::{ColorAlpha, Hsl, Rgb};
 use indexmap::IndexMap;
 
 use crate::{
-    color::format::{format_alpha_hex, format_alpha_hex_stripped},
+    color::format::{alpha_f64, format_alpha_hex, format_alpha_hex_stripped},
     parser::{
         engine::{BinaryOperator, Expression, SpannedBinaryOperator, SpannedExpr, Template},
+        helpers::{bind_filter_args, FilterArgument},
         BinaryOperatorError, Error, FilterError, FilterReturnType, IfError, KeywordError,
         LoopError, ParseErrorKind, SpannedValue, Value,
     },
 };
 
 use crate::color::format::{
-    format_hex, format_hex_alpha, format_hex_alpha_stripped, format_hex_stripped, format_hsl,
-    format_hsla, format_rgb, format_rgba,
+    format_ansi256, format_argb_hex, format_argb_int, format_hex, format_hex_alpha,
+    format_hex_alpha_stripped, format_hex_stripped, format_hsl, format_hsla, format_hwb,
+    format_lab, format_lch, format_rgb, format_rgb_float, format_rgb_percent, format_rgba,
+    format_rgba_float, format_xterm,
 };
 
 use super::Engine;
@@ -29,6 +32,16 @@ pub const FORMATS: &[&str] = &[
     "rgba",
     "hsl",
     "hsla",
+    "argb_hex",
+    "argb_int",
+    "rgb_float",
+    "rgba_float",
+    "rgb_percent",
+    "hwb",
+    "lab",
+    "lch",
+    "ansi256",
+    "xterm",
     "red",
     "green",
     "blue",
@@ -65,6 +78,16 @@ pub fn format_color(base_color: Rgb, format: &str) -> Option<Value> {
             "rgba" => Some(format_rgba(&base_color).into()),
             "hsl" => Some(format_hsl(&hsl_color).into()),
             "hsla" => Some(format_hsla(&hsl_color).into()),
+            "argb_hex" => Some(format_argb_hex(&base_color).into()),
+            "argb_int" => Some(Value::Int(format_argb_int(&base_color))),
+            "rgb_float" => Some(format_rgb_float(&base_color).into()),
+            "rgba_float" => Some(format_rgba_float(&base_color).into()),
+            "rgb_percent" => Some(format_rgb_percent(&base_color).into()),
+            "hwb" => Some(format_hwb(&base_color).into()),
+            "lab" => Some(format_lab(&base_color).into()),
+            "lch" => Some(format_lch(&base_color).into()),
+            "ansi256" => Some(Value::Int(format_ansi256(&base_color))),
+            "xterm" => Some(format_xterm(&base_color).into()),
             "red" => Some(Value::Int(base_color.red() as i64)),
             "green" => Some(Value::Int(base_color.green() as i64)),
             "blue" => Some(Value::Int(base_color.blue() as i64)),
@@ -100,6 +123,34 @@ pub fn format_color_all(base_color: Rgb) -> IndexMap<String, Value> {
     map.insert("rgba".to_string(), Value::Ident(format_rgba(&base_color)));
     map.insert("hsl".to_string(), Value::Ident(format_hsl(&hsl_color)));
     map.insert("hsla".to_string(), Value::Ident(format_hsla(&hsl_color)));
+    map.insert(
+        "argb_hex".to_string(),
+        Value::Ident(format_argb_hex(&base_color)),
+    );
+    map.insert(
+        "argb_int".to_string(),
+        Value::Int(format_argb_int(&base_color)),
+    );
+    map.insert(
+        "rgb_float".to_string(),
+        Value::Ident(format_rgb_float(&base_color)),
+    );
+    map.insert(
+        "rgba_float".to_string(),
+        Value::Ident(format_rgba_float(&base_color)),
+    );
+    map.insert(
+        "rgb_percent".to_string(),
+        Value::Ident(format_rgb_percent(&base_color)),
+    );
+    map.insert("hwb".to_string(), Value::Ident(format_hwb(&base_color)));
+    map.insert("lab".to_string(), Value::Ident(format_lab(&base_color)));
+    map.insert("lch".to_string(), Value::Ident(format_lch(&base_color)));
+    map.insert(
+        "ansi256".to_string(),
+        Value::Int(format_ansi256(&base_color)),
+    );
+    map.insert("xterm".to_string(), Value::Ident(format_xterm(&base_color)));
     map.insert("red".to_string(), Value::Int(base_color.red() as i64));
     map.insert("green".to_string(), Value::Int(base_color.green() as i64));
     map.insert("blue".to_string(), Value::Int(base_color.blue() as i64));
@@ -128,6 +179,143 @@ impl Engine {
         self.build_string(&template.ast, &self.sources[template.source_id], &name)
     }
 
+    /// Formats a color with one of the built-in formats, or a custom one added with `add_format`.
+    pub fn format_color(&self, base_color: Rgb, format: &str) -> Option<Value> {
+        if let Some(value) = format_color(base_color.clone(), format) {
+            return Some(value);
+        }
+
+        let name = format!("format:{}", format);
+        let template = self.templates.get(&name)?;
+
+        if self.expanding.borrow().contains(&name) {
+            self.errors.add(Error::ParseError {
+                kind: ParseErrorKind::Keyword(KeywordError::RecursiveFormat {
+                    format: format.to_string(),
+                }),
+                span: SimpleSpan::from(0..self.sources[template.source_id].len()),
+                name,
+            });
+            return Some(Value::Ident(String::new()));
+        }
+
+        let hsl_color = Hsl::from(&base_color);
+
+        {
+            let mut runtime = self.runtime.borrow_mut();
+            runtime.push_scope();
+            runtime.insert("red", Value::Int(base_color.red() as i64));
+            runtime.insert("green", Value::Int(base_color.green() as i64));
+            runtime.insert("blue", Value::Int(base_color.blue() as i64));
+            runtime.insert("alpha", Value::Float(alpha_f64(base_color.alpha())));
+            runtime.insert("red_f", Value::Float(base_color.red() / 255.0));
+            runtime.insert("green_f", Value::Float(base_color.green() / 255.0));
+            runtime.insert("blue_f", Value::Float(base_color.blue() / 255.0));
+            runtime.insert("hue", Value::Int(hsl_color.hue() as i64));
+            runtime.insert("saturation", Value::Int(hsl_color.saturation() as i64));
+            runtime.insert("lightness", Value::Int(hsl_color.lightness() as i64));
+        }
+
+        self.expanding.borrow_mut().push(name.clone());
+        let res = self.generate_template(template, name);
+        self.expanding.borrow_mut().pop();
+        self.runtime.borrow_mut().pop_scope();
+
+        Some(Value::Ident(res))
+    }
+
+    /// Like [`format_color_all`], including the custom formats that aren't being rendered already.
+    pub fn format_color_all(&self, base_color: Rgb) -> IndexMap<String, Value> {
+        let mut map = format_color_all(base_color.clone());
+
+        for format in self.custom_formats() {
+            if self
+                .expanding
+                .borrow()
+                .contains(&format!("format:{}", format))
+            {
+                continue;
+            }
+            if let Some(value) = self.format_color(base_color.clone(), &format) {
+                map.insert(format, value);
+            }
+        }
+
+        map
+    }
+
+    /// The names of the formats added with `add_format`, sorted.
+    pub fn custom_formats(&self) -> Vec<String> {
+        let mut formats: Vec<String> = self
+            .templates
+            .keys()
+            .filter_map(|name| name.strip_prefix("format:"))
+            .map(str::to_string)
+            .collect();
+        formats.sort();
+        formats
+    }
+
+    /// All formats colors can be written in, the built-in ones first.
+    pub fn formats(&self) -> Vec<String> {
+        FORMATS
+            .iter()
+            .map(|format| format.to_string())
+            .chain(self.custom_formats())
+            .collect()
+    }
+
+    /// Runs the filters of an alias added with `add_filter_alias`, with its arguments in scope.
+    pub(crate) fn apply_filter_alias(
+        &self,
+        name: &str,
+        keywords: &[&str],
+        args: &[SpannedValue],
+        original: FilterReturnType,
+    ) -> Result<FilterReturnType, FilterError> {
+        let template = self
+            .templates
+            .get(name)
+            .ok_or_else(|| FilterError::FilterNotFound {
+                filter: name.to_string(),
+            })?;
+        let source = &self.sources[template.source_id];
+        let filter = name.trim_start_matches("filter:").to_string();
+
+        let Some(Expression::KeywordWithFilters { keyword, filters }) =
+            template.ast.first().map(|expr| &expr.expr)
+        else {
+            return Err(FilterError::InvalidAlias { filter });
+        };
+
+        if self.expanding.borrow().iter().any(|n| n == name) {
+            return Err(FilterError::RecursiveAlias { filter });
+        }
+
+        {
+            let mut runtime = self.runtime.borrow_mut();
+            runtime.push_scope();
+            for (i, arg) in args.iter().enumerate() {
+                runtime.insert(format!("alias_arg_{}", i + 1), arg.value.clone());
+            }
+        }
+
+        self.expanding.borrow_mut().push(name.to_string());
+        let res = self.get_replacement_filter(
+            original,
+            (!keywords.is_empty()).then_some(keywords),
+            filters,
+            source,
+            keyword.span,
+            name,
+            false,
+        );
+        self.expanding.borrow_mut().pop();
+        self.runtime.borrow_mut().pop_scope();
+
+        Ok(res)
+    }
+
     fn build_string(&self, exprs: &[Box<SpannedExpr>], source: &String, name: &str) -> String {
         let src = &mut String::from("");
 
@@ -175,7 +363,6 @@ impl Engine {
                 let format_color = true;
 
                 match &iter.expr {
-                    //     Expression::KeywordWithFilters { keyword, filters } => todo!(),
                     Expression::Range { start, end } => {
                         for (index, i) in (*start..*end).enumerate() {
                             self.runtime.borrow_mut().push_scope();
@@ -208,53 +395,14 @@ impl Engine {
                             return;
                         };
 
-                        match values {
-                            Value::Map(map) => {
-                                let res = self.eval_map(map, body, var, source, iter.span, name);
-                                src.push_str(&res);
-                            }
-                            Value::LazyColor { color, scheme: _ } | Value::Color(color) => {
-                                let formats = format_color_all(color);
-                                let res =
-                                    self.eval_map(formats, body, var, source, iter.span, name);
-                                src.push_str(&res);
-                            }
-                            Value::Array(arr) => {
-                                let total = arr.len();
-
-                                for (index, item) in arr.iter().enumerate() {
-                                    self.runtime.borrow_mut().push_scope();
-
-                                    if var.len() == 1 {
-                                        self.runtime
-                                            .borrow_mut()
-                                            .insert(var[0].value.to_string(), item.clone());
-                                    } else {
-                                        self.errors.add(Error::ParseError {
-                                            kind: ParseErrorKind::Loop(
-                                                LoopError::TooManyLoopVariablesArray,
-                                            ),
-                                            span: iter.span,
-                                            name: name.to_string(),
-                                        });
-                                    }
-
-                                    self.add_loop_variables(index, total);
-
-                                    src.push_str(&self.eval_loop_body(body.clone(), source, name));
-                                    self.runtime.borrow_mut().pop_scope();
-                                }
-                            }
-                            _ => {
-                                self.errors.add(Error::ParseError {
-                                    kind: ParseErrorKind::Loop(
-                                        crate::parser::LoopError::LoopOverNonIterableValue,
-                                    ),
-                                    span: iter.span,
-                                    name: name.to_string(),
-                                });
-                            }
-                        }
+                        let res = self.eval_iterable(values, body, var, source, iter.span, name);
+                        src.push_str(&res);
+                    }
+                    Expression::KeywordWithFilters { .. } => {
+                        let values = self.get_value(iter, source, false, false, name);
+
+                        let res = self.eval_iterable(values, body, var, source, iter.span, name);
+                        src.push_str(&res);
                     }
                     _ => {}
                 }
@@ -288,6 +436,7 @@ impl Engine {
                 }
             }
             Expression::Filter { name: _, args: _ } => unreachable!(),
+            Expression::NamedArg { name: _, value: _ } => unreachable!(),
             Expression::Range { start: _, end: _ } => unreachable!(),
             Expression::LiteralValue { value: _ } => unreachable!(),
             Expression::BinaryOp {
@@ -311,10 +460,63 @@ impl Engine {
         self.runtime.borrow_mut().insert("loop", Value::Map(map));
     }
 
+    fn eval_iterable(
+        &self,
+        values: Value,
+        body: &[Box<SpannedExpr>],
+        var: &Vec<SpannedValue>,
+        source: &String,
+        span: SimpleSpan,
+        name: &str,
+    ) -> String {
+        match values {
+            Value::Map(map) => self.eval_map(map, body, var, source, span, name),
+            Value::LazyColor { color, scheme: _ } | Value::Color(color) => {
+                let formats = self.format_color_all(color);
+                self.eval_map(formats, body, var, source, span, name)
+            }
+            Value::Array(arr) => {
+                let mut output = String::from("");
+                let total = arr.len();
+
+                for (index, item) in arr.iter().enumerate() {
+                    self.runtime.borrow_mut().push_scope();
+
+                    if var.len() == 1 {
+                        self.runtime
+                            .borrow_mut()
+                            .insert(var[0].value.to_string(), item.clone());
+                    } else {
+                        self.errors.add(Error::ParseError {
+                            kind: ParseErrorKind::Loop(LoopError::TooManyLoopVariablesArray),
+                            span,
+                            name: name.to_string(),
+                        });
+                    }
+
+                    self.add_loop_variables(index, total);
+
+                    output.push_str(&self.eval_loop_body(body.to_vec(), source, name));
+                    self.runtime.borrow_mut().pop_scope();
+                }
+
+                output
+            }
+            _ => {
+                self.errors.add(Error::ParseError {
+                    kind: ParseErrorKind::Loop(crate::parser::LoopError::LoopOverNonIterableValue),
+                    span,
+                    name: name.to_string(),
+                });
+                String::from("")
+            }
+        }
+    }
+
     fn eval_map(
         &self,
         map: IndexMap<String, Value>,
-        body: &Vec<Box<SpannedExpr>>,
+        body: &[Box<SpannedExpr>],
         var: &Vec<SpannedValue>,
         source: &String,
         span: SimpleSpan,
@@ -347,7 +549,7 @@ impl Engine {
 
             self.add_loop_variables(index, total);
 
-            output.push_str(&self.eval_loop_body(body.clone(), source, name));
+            output.push_str(&self.eval_loop_body(body.to_vec(), source, name));
 
             self.runtime.borrow_mut().pop_scope();
         }
@@ -377,12 +579,12 @@ impl Engine {
             Ok(v) => {
                 if get_color_value {
                     match v {
-                        Value::Color(c) => format_color(c, self.get_format(keywords)).unwrap(),
-                        Value::HslColor(c) => {
-                            format_color(c.into(), self.get_format(keywords)).unwrap()
-                        }
+                        Value::Color(c) => self.format_color(c, self.get_format(keywords)).unwrap(),
+                        Value::HslColor(c) => self
+                            .format_color(c.into(), self.get_format(keywords))
+                            .unwrap(),
                         Value::LazyColor { color: c, .. } => {
-                            format_color(c, self.get_format(keywords)).unwrap()
+                            self.format_color(c, self.get_format(keywords)).unwrap()
                         }
                         _ => v,
                     }
@@ -471,7 +673,7 @@ impl Engine {
                 self.get_value(&keywords, source, format_value, get_color_value, name)
             }
             Expression::KeywordWithFilters { keyword, filters } => {
-                let value = self.get_value(&keyword, source, false, get_color_value, name);
+                let value = self.get_value(keyword, source, false, false, name);
                 let keywords = keyword.expr.as_keywords(source);
                 Value::from(self.get_replacement_filter(
                     value.into(),
@@ -561,9 +763,7 @@ impl Engine {
             }
             _ => {
                 let error = Error::ParseError {
-                    kind: ParseErrorKind::Loop(
-                        crate::parser::LoopError::LoopOverNonIterableValue,
-                    ),
+                    kind: ParseErrorKind::Loop(crate::parser::LoopError::LoopOverNonIterableValue),
                     span: expr.span,
                     name: name.to_string(),
                 };
@@ -588,6 +788,9 @@ impl Engine {
             FilterReturnType::Hsl(_) => true,
             FilterReturnType::String(_) => false,
             FilterReturnType::Bool(_) => false,
+            FilterReturnType::Int(_) | FilterReturnType::Float(_) => false,
+            FilterReturnType::Array(_) => false,
+            FilterReturnType::Map(_) => false,
         };
 
         let (format, is_format_empty) = match keywords {
@@ -604,57 +807,24 @@ impl Engine {
                 let mut args_resolved = vec![];
                 for arg in args {
                     match &arg.expr {
-                        Expression::Keyword { keywords } => args_resolved.push(SpannedValue {
-                            value: self.get_value(keywords, source, false, false, name),
-                            span: arg.span,
-                        }),
-                        Expression::KeywordWithFilters { keyword, filters } => {
-                            let value = self.get_value(&keyword, source, false, false, name);
-                            let keywords = keyword.expr.as_keywords(source);
-                            args_resolved.push(SpannedValue {
-                                value: self
-                                    .get_replacement_filter(
-                                        value.into(),
-                                        keywords.as_deref(),
-                                        filters,
-                                        source,
-                                        span,
-                                        name,
-                                        false,
-                                    )
-                                    .into(),
-                                span: arg.span,
-                            });
-                        }
-                        Expression::LiteralValue { value } => args_resolved.push(value.clone()),
-                        Expression::BinaryOp { lhs, op, rhs } => {
-                            args_resolved.push(SpannedValue {
-                                value: self
-                                    .replace_binary_op(lhs, *op, rhs, source, arg.span, name),
-                                span: arg.span,
-                            });
-                        }
-                        Expression::If { .. } => {
-                            let val = self.get_value(arg, source, false, false, name);
-                            match val {
-                                Value::Array(array) => {
-                                    for value in array {
-                                        args_resolved.push(SpannedValue {
-                                            value,
-                                            span: arg.span,
-                                        })
-                                    }
-                                }
-                                v => {
-                                    args_resolved.push(SpannedValue {
-                                        value: v,
-                                        span: arg.span,
-                                    });
-                                }
+                        Expression::NamedArg {
+                            name: arg_name,
+                            value,
+                        } => {
+                            for resolved in self.resolve_filter_arg(value, source, span, name) {
+                                args_resolved.push(FilterArgument {
+                                    name: Some((get_str(source, arg_name).to_string(), *arg_name)),
+                                    value: resolved,
+                                });
                             }
                         }
                         _ => {
-                            panic!("Unsupported filter arg")
+                            for resolved in self.resolve_filter_arg(arg, source, span, name) {
+                                args_resolved.push(FilterArgument {
+                                    name: None,
+                                    value: resolved,
+                                });
+                            }
                         }
                     }
                 }
@@ -669,6 +839,8 @@ impl Engine {
                         ),
                         "rgb" => (true, Some("rgba")),
                         "hsl" => (true, Some("hsla")),
+                        "rgb_float" => (true, Some("rgba_float")),
+                        "ansi256" | "xterm" => (true, Some("argb_hex")),
                         _ => (false, None),
                     };
 
@@ -685,7 +857,7 @@ impl Engine {
 
                 current_value = match self.apply_filter(
                     filter_name,
-                    &args_resolved,
+                    args_resolved,
                     keywords.unwrap_or(&vec![]),
                     current_value,
                     filter.span,
@@ -694,8 +866,8 @@ impl Engine {
                     Ok(val) => val,
                     Err(e) => {
                         let error = Error::ParseError {
+                            span: e.span().unwrap_or(filter.span),
                             kind: ParseErrorKind::Filter(e),
-                            span: filter.span,
                             name: name.to_string(),
                         };
                         self.errors.add(error);
@@ -715,12 +887,12 @@ impl Engine {
 
         match current_value {
             FilterReturnType::String(_) => current_value,
-            FilterReturnType::Rgb(argb) => match format_color(argb, format) {
+            FilterReturnType::Rgb(argb) => match self.format_color(argb, format) {
                 Some(v) => FilterReturnType::String(v.to_string()),
                 None => {
                     let error = Error::ParseError {
                         kind: ParseErrorKind::Keyword(KeywordError::InvalidFormat {
-                            formats: FORMATS,
+                            formats: self.formats(),
                         }),
                         span,
                         name: name.to_string(),
@@ -729,12 +901,12 @@ impl Engine {
                     FilterReturnType::String(String::from(""))
                 }
             },
-            FilterReturnType::Hsl(hsl) => match format_color(hsl.into(), format) {
+            FilterReturnType::Hsl(hsl) => match self.format_color(hsl.into(), format) {
                 Some(v) => FilterReturnType::String(v.to_string()),
                 None => {
                     let error = Error::ParseError {
                         kind: ParseErrorKind::Keyword(KeywordError::InvalidFormat {
-                            formats: FORMATS,
+                            formats: self.formats(),
                         }),
                         span,
                         name: name.to_string(),
@@ -744,20 +916,121 @@ impl Engine {
                 }
             },
             FilterReturnType::Bool(_) => current_value,
+            FilterReturnType::Int(_) | FilterReturnType::Float(_) => current_value,
+            FilterReturnType::Map(_) => current_value,
+            FilterReturnType::Array(values) => FilterReturnType::String(
+                values
+                    .into_iter()
+                    .map(|value| match value {
+                        Value::Color(color) | Value::LazyColor { color, .. } => self
+                            .format_color(color, format)
+                            .map(|v| v.to_string())
+                            .unwrap_or_default(),
+                        Value::HslColor(color) => self
+                            .format_color(color.into(), format)
+                            .map(|v| v.to_string())
+                            .unwrap_or_default(),
+                        v => v.to_string(),
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", "),
+            ),
         }
     }
 
+    fn resolve_filter_arg(
+        &self,
+        arg: &SpannedExpr,
+        source: &String,
+        span: SimpleSpan,
+        name: &str,
+    ) -> Vec<SpannedValue> {
+        let mut values = vec![];
+
+        match &arg.expr {
+            Expression::Keyword { keywords } => values.push(SpannedValue {
+                value: self.get_value(keywords, source, false, false, name),
+                span: arg.span,
+            }),
+            Expression::KeywordWithFilters { keyword, filters } => {
+                let value = self.get_value(keyword, source, false, false, name);
+                let keywords = keyword.expr.as_keywords(source);
+                values.push(SpannedValue {
+                    value: self
+                        .get_replacement_filter(
+                            value.into(),
+                            keywords.as_deref(),
+                            filters,
+                            source,
+                            span,
+                            name,
+                            false,
+                        )
+                        .into(),
+                    span: arg.span,
+                });
+            }
+            Expression::LiteralValue { value } => values.push(value.clone()),
+            Expression::BinaryOp { lhs, op, rhs } => {
+                values.push(SpannedValue {
+                    value: self.replace_binary_op(lhs, *op, rhs, source, arg.span, name),
+                    span: arg.span,
+                });
+            }
+            Expression::If { .. } => {
+                let val = self.get_value(arg, source, false, false, name);
+                match val {
+                    Value::Array(array) => {
+                        for value in array {
+                            values.push(SpannedValue {
+                                value,
+                                span: arg.span,
+                            })
+                        }
+                    }
+                    v => {
+                        values.push(SpannedValue {
+                            value: v,
+                            span: arg.span,
+                        });
+                    }
+                }
+            }
+            _ => {
+                panic!("Unsupported filter arg")
+            }
+        }
+
+        values
+    }
+
     fn apply_filter(
         &self,
         filtername: &str,
-        args: &[SpannedValue],
+        args: Vec<FilterArgument>,
         keywords: &[&str],
         input: FilterReturnType,
         span: SimpleSpan,
         name: &str,
     ) -> Result<FilterReturnType, FilterError> {
         match self.filters.get(filtername) {
-            Some(f) => f(keywords, args, input, self),
+            Some(f) => {
+                let args = match self.get_filter_params(filtername) {
+                    Some(params) => bind_filter_args(params, args, span)?,
+                    None => args
+                        .into_iter()
+                        .map(|arg| match arg.name {
+                            Some((name, span)) => Err(FilterError::UnknownArgument {
+                                name,
+                                expected: String::new(),
+                                span,
+                            }),
+                            None => Ok(arg.value),
+                        })
+                        .collect::<Result<Vec<SpannedValue>, FilterError>>()?,
+                };
+                f(keywords, &args, input, self)
+            }
             None => {
                 let error = Error::ParseError {
                     kind: ParseErrorKind::Filter(FilterError::FilterNotFound {
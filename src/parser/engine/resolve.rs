@@ -1,13 +1,11 @@
 use chumsky::span::SimpleSpan;
+use colorsys::Rgb;
 
 use super::Engine;
 
 use crate::{
     color::parse::parse_css_color,
-    parser::{
-        engine::{format_color, FORMATS},
-        Error, ParseErrorKind, Value,
-    },
+    parser::{Error, ParseErrorKind, Value},
 };
 
 impl Engine {
@@ -29,12 +27,12 @@ impl Engine {
             }
         };
         if format_value {
-            let res = match format_color(color, format) {
+            let res = match self.format_color(color, format) {
                 Some(v) => v,
                 None => {
                     return Err(Error::ParseError {
                         kind: ParseErrorKind::Keyword(crate::parser::KeywordError::InvalidFormat {
-                            formats: FORMATS,
+                            formats: self.formats(),
                         }),
                         span,
                         name: name.to_string(),
@@ -100,7 +98,7 @@ impl Engine {
                 Value::LazyColor { color, .. } => {
                     current = if format_value {
                         Value::Ident(
-                            format_color(color, next_key)
+                            self.format_color(color, next_key)
                                 .ok_or(Error::ResolveError {
                                     span,
                                     name: name.to_string(),
@@ -114,7 +112,7 @@ impl Engine {
                 Value::Color(color) => {
                     current = if format_value {
                         Value::Ident(
-                            format_color(color, next_key)
+                            self.format_color(color, next_key)
                                 .ok_or(Error::ResolveError {
                                     span,
                                     name: name.to_string(),
@@ -137,6 +135,25 @@ impl Engine {
         Ok(current)
     }
 
+    /// Resolves a path like `["colors", "surface_tint", "dark"]` from the context into a color.
+    pub fn resolve_color(&self, path: &[&str]) -> Option<Rgb> {
+        let value = self
+            .resolve_path(path.iter().copied(), false, SimpleSpan::from(0..0), "")
+            .ok()?;
+
+        let value = match value {
+            Value::Map(mut map) => map.swap_remove("color")?,
+            v => v,
+        };
+
+        match value {
+            Value::Color(color) | Value::LazyColor { color, .. } => Some(color),
+            Value::HslColor(color) => Some(color.into()),
+            Value::Ident(string) => parse_css_color(&string).ok(),
+            _ => None,
+        }
+    }
+
     pub fn get_format<'a>(&self, keywords: &[&'a str]) -> &'a str {
         keywords
             .last()
@@ -118,7 +118,9 @@ pub enum LoopError {
 #[derive(Debug, ThisError)]
 pub enum KeywordError {
     #[error("The format provided is not valid. Available formats are: {formats:?}")]
-    InvalidFormat { formats: &'static [&'static str] },
+    InvalidFormat { formats: Vec<String> },
+    #[error("The custom format '{format}' refers to itself")]
+    RecursiveFormat { format: String },
     #[error("Invalid color mode. The color mode can only be one of: [dark, light, default]")]
     ColorDoesNotExist,
     #[error("The format for colors is 'colors.<color>.<scheme>.<format>'")]
@@ -139,17 +141,71 @@ pub enum FilterError {
     ColorFilterOnString,
     #[error("Cannot use color filters on a boolean value")]
     ColorFilterOnBool,
+    #[error("Cannot use color filters on a number")]
+    ColorFilterOnNumber,
+    #[error("Cannot use this filter on an array, consider looping over it with a for loop")]
+    FilterOnArray,
+    #[error("Cannot use this filter on a map, consider looping over it with a for loop")]
+    FilterOnMap,
     #[error("Could not find the filter: {filter}")]
     FilterNotFound { filter: String },
     #[error("Invalid String, expected one of: [{expected}]")]
     UnexpectedStringValue { expected: String, span: SimpleSpan },
-    #[error("Invalid format for the 'format' filter, expected one of: {expected:?}")]
+    #[error("Invalid color format, expected one of: {expected:?}")]
     InvalidFormatString {
-        expected: &'static [&'static str],
+        expected: Vec<String>,
         span: SimpleSpan,
     },
+    #[error("Unknown color format '{format}', expected one of: {expected:?}")]
+    UnknownFormat {
+        format: String,
+        expected: Vec<String>,
+    },
     #[error("You should not use the set_alpha filter with a format that doesn't have an alpha channel. Consider using one of these formats instead: [{replacement}]")]
     SetAlphaOnNonAlphaFormat { replacement: &'static str },
+    #[error("The '{color}' color could not be found in the context, consider passing a color to the filter")]
+    MissingContextColor { color: &'static str },
+    #[error("This filter can only be used on Arrays and Maps, found '{actual}'")]
+    ExpectedCollection { actual: String },
+    #[error("Could not get a color from a '{value}' value")]
+    NoColorInValue { value: String },
+    #[error("Invalid regex: {error}")]
+    InvalidRegex { error: String, span: SimpleSpan },
+    #[error("This filter can only be used on numbers, found '{actual}'")]
+    ExpectedNumber { actual: String },
+    #[error("Invalid number format '{format}', expected a single value like '%.2f' or '{{:02x}}'")]
+    InvalidNumberFormat { format: String, span: SimpleSpan },
+    #[error("Missing the '{name}' argument")]
+    MissingArgument { name: String, span: SimpleSpan },
+    #[error("Unknown argument '{name}', expected one of: [{expected}]")]
+    UnknownArgument {
+        name: String,
+        expected: String,
+        span: SimpleSpan,
+    },
+    #[error("The '{name}' argument was provided more than once")]
+    DuplicateArgument { name: String, span: SimpleSpan },
+    #[error("Too many arguments, this filter takes at most {expected}")]
+    TooManyArguments { expected: usize, span: SimpleSpan },
+    #[error("Positional arguments have to come before named arguments")]
+    PositionalAfterNamed { span: SimpleSpan },
+    #[error("Expected a value between {min} and {max}, found {actual}")]
+    ArgumentOutOfRange {
+        min: i64,
+        max: i64,
+        actual: i64,
+        span: SimpleSpan,
+    },
+    #[error("The custom filter '{filter}' should be a list of filters, like 'lighten: 10 | set_alpha: 0.5'")]
+    InvalidAlias { filter: String },
+    #[error("The custom filter '{filter}' refers to itself")]
+    RecursiveAlias { filter: String },
+    #[error("Could not find the palette: {palette}, palettes can be defined in [config.palettes]")]
+    PaletteNotFound { palette: String },
+    #[error("The '{plugin}' plugin failed: {message}")]
+    PluginError { plugin: String, message: String },
+    #[error("The '{script}' script failed: {message}")]
+    ScriptError { script: String, message: String },
 }
 
 impl Error {
@@ -208,10 +264,49 @@ impl FilterError {
             FilterError::InvalidArgumentType { .. } => "InvalidArgumentType",
             FilterError::ColorFilterOnString => "ColorFilterOnString",
             FilterError::ColorFilterOnBool => "ColorFilterOnBool",
+            FilterError::ColorFilterOnNumber => "ColorFilterOnNumber",
+            FilterError::FilterOnArray => "FilterOnArray",
+            FilterError::FilterOnMap => "FilterOnMap",
             FilterError::FilterNotFound { .. } => "FilterNotFound",
             FilterError::UnexpectedStringValue { .. } => "UnexpectedStringValue",
             FilterError::InvalidFormatString { .. } => "InvalidFormatString",
+            FilterError::UnknownFormat { .. } => "UnknownFormat",
             FilterError::SetAlphaOnNonAlphaFormat { .. } => "SetAlphaOnNonAlphaFormat",
+            FilterError::MissingContextColor { .. } => "MissingContextColor",
+            FilterError::ArgumentOutOfRange { .. } => "ArgumentOutOfRange",
+            FilterError::InvalidAlias { .. } => "InvalidAlias",
+            FilterError::RecursiveAlias { .. } => "RecursiveAlias",
+            FilterError::PaletteNotFound { .. } => "PaletteNotFound",
+            FilterError::PluginError { .. } => "PluginError",
+            FilterError::ScriptError { .. } => "ScriptError",
+            FilterError::ExpectedCollection { .. } => "ExpectedCollection",
+            FilterError::NoColorInValue { .. } => "NoColorInValue",
+            FilterError::InvalidRegex { .. } => "InvalidRegex",
+            FilterError::ExpectedNumber { .. } => "ExpectedNumber",
+            FilterError::InvalidNumberFormat { .. } => "InvalidNumberFormat",
+            FilterError::MissingArgument { .. } => "MissingArgument",
+            FilterError::UnknownArgument { .. } => "UnknownArgument",
+            FilterError::DuplicateArgument { .. } => "DuplicateArgument",
+            FilterError::TooManyArguments { .. } => "TooManyArguments",
+            FilterError::PositionalAfterNamed { .. } => "PositionalAfterNamed",
+        }
+    }
+
+    /// The span of the argument that caused the error, if it is known.
+    pub fn span(&self) -> Option<SimpleSpan> {
+        match self {
+            FilterError::InvalidArgumentType { span, .. }
+            | FilterError::UnexpectedStringValue { span, .. }
+            | FilterError::InvalidFormatString { span, .. }
+            | FilterError::InvalidRegex { span, .. }
+            | FilterError::InvalidNumberFormat { span, .. }
+            | FilterError::MissingArgument { span, .. }
+            | FilterError::UnknownArgument { span, .. }
+            | FilterError::DuplicateArgument { span, .. }
+            | FilterError::TooManyArguments { span, .. }
+            | FilterError::ArgumentOutOfRange { span, .. }
+            | FilterError::PositionalAfterNamed { span } => Some(*span),
+            _ => None,
         }
     }
 }
@@ -220,6 +315,7 @@ impl KeywordError {
     pub fn name(&self) -> &str {
         match self {
             KeywordError::InvalidFormat { .. } => "InvalidFormat",
+            KeywordError::RecursiveFormat { .. } => "RecursiveFormat",
             KeywordError::ColorDoesNotExist => "ColorDoesNotExist",
             KeywordError::InvalidColorDefinition => "InvalidColorDefinition",
         }
@@ -0,0 +1,397 @@
+use std::collections::{BTreeMap, HashSet};
+
+use colorsys::Rgb;
+use indexmap::IndexMap;
+
+use crate::parser::{
+    helpers::{filter_docs, FilterDoc},
+    Engine,
+};
+
+const EXAMPLE_TEMPLATE: &str = "filter-docs-example";
+
+/// The palettes the examples use, like the ones in the example config.
+const EXAMPLE_PALETTES: &[(&str, &[(&str, &str)])] = &[(
+    "folders",
+    &[
+        ("blue", "#5294e2"),
+        ("green", "#87b158"),
+        ("orange", "#ee923a"),
+        ("red", "#e25252"),
+        ("violet", "#a063ce"),
+        ("yellow", "#f9bd30"),
+    ],
+)];
+
+/// The parts of the HTML description written in `register_filters!`.
+#[derive(Debug, Default, PartialEq)]
+struct Sections<'a> {
+    paragraphs: Vec<&'a str>,
+    arguments: Vec<&'a str>,
+    examples: Vec<String>,
+}
+
+/// Every piece of text between `start` and `end`.
+fn find_all<'a>(text: &'a str, start: &str, end: &str) -> Vec<&'a str> {
+    let mut found = Vec::new();
+    let mut rest = text;
+
+    while let Some(i) = rest.find(start) {
+        rest = &rest[i + start.len()..];
+        let Some(j) = rest.find(end) else { break };
+        found.push(&rest[..j]);
+        rest = &rest[j + end.len()..];
+    }
+
+    found
+}
+
+fn decode_entities(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&amp;", "&")
+}
+
+fn sections(description: &str) -> Sections<'_> {
+    let paragraphs = find_all(description, "<p>", "</p>")
+        .into_iter()
+        // The `Arguments:` and `Example:` headings
+        .filter(|p| !(p.starts_with("<strong>") && p.ends_with(":</strong>")))
+        .collect();
+
+    let arguments = find_all(description, "<li>", "</li>")
+        .into_iter()
+        .map(|li| {
+            li.split_once(" - ")
+                .map_or(li, |(_, description)| description)
+        })
+        // Filters without arguments list `None`
+        .filter(|argument| argument.trim() != "None")
+        .collect();
+
+    let examples = find_all(description, "<pre class=\"code-block\">", "</pre>")
+        .into_iter()
+        .map(|pre| {
+            let code = pre.split_once('>').map_or(pre, |(_, code)| code);
+            decode_entities(code.strip_suffix("</code>").unwrap_or(code))
+        })
+        .collect();
+
+    Sections {
+        paragraphs,
+        arguments,
+        examples,
+    }
+}
+
+/// Turns the inline HTML of the docs into plain text with the given markers around code and
+/// strong text.
+fn convert_inline(html: &str, code: (&str, &str), strong: (&str, &str)) -> String {
+    let text = html
+        .replace("<code>", code.0)
+        .replace("</code>", code.1)
+        .replace("<strong>", strong.0)
+        .replace("</strong>", strong.1);
+
+    decode_entities(&text)
+        .split_whitespace()
+        .collect::<Vec<&str>>()
+        .join(" ")
+}
+
+fn markdown_inline(html: &str) -> String {
+    convert_inline(html, ("`", "`"), ("**", "**")).replace('|', "\\|")
+}
+
+fn man_escape(text: &str) -> String {
+    text.replace('\\', "\\e").replace('-', "\\-")
+}
+
+fn man_inline(html: &str) -> String {
+    let text = convert_inline(&man_escape(html), ("\\fB", "\\fR"), ("\\fB", "\\fR"));
+
+    // Lines starting with a dot or quote would be read as requests
+    if text.starts_with('.') || text.starts_with('\'') {
+        format!("\\&{}", text)
+    } else {
+        text
+    }
+}
+
+/// Docs grouped by category, in the order the filters were registered.
+fn grouped() -> BTreeMap<&'static str, Vec<FilterDoc>> {
+    let mut grouped: BTreeMap<&str, Vec<FilterDoc>> = BTreeMap::new();
+
+    for doc in filter_docs() {
+        grouped.entry(doc.category).or_default().push(doc);
+    }
+
+    grouped
+}
+
+/// The description of each parameter, when the HTML lists one for every parameter.
+fn argument_descriptions<'a>(doc: &FilterDoc, sections: &Sections<'a>) -> Vec<Option<&'a str>> {
+    doc.params
+        .iter()
+        .enumerate()
+        .map(|(i, _)| (sections.arguments.len() == doc.params.len()).then(|| sections.arguments[i]))
+        .collect()
+}
+
+pub fn filters_to_markdown() -> String {
+    let mut out = String::from("# Filters\n\n");
+
+    for (category, docs) in grouped() {
+        out.push_str(&format!("## {}\n\n", category));
+
+        for doc in docs {
+            let sections = sections(doc.description);
+            out.push_str(&format!("### `{}`\n\n", doc.name));
+
+            for paragraph in &sections.paragraphs {
+                out.push_str(&format!("{}\n\n", markdown_inline(paragraph)));
+            }
+
+            if !doc.params.is_empty() {
+                out.push_str("| Argument | Type | Default | Description |\n");
+                out.push_str("| --- | --- | --- | --- |\n");
+
+                let descriptions = argument_descriptions(&doc, &sections);
+                for (param, description) in doc.params.iter().zip(descriptions) {
+                    let default = match (param.required, param.default) {
+                        (_, Some(default)) => format!("`{}`", default.replace('|', "\\|")),
+                        (true, None) => "required".to_string(),
+                        (false, None) => "none".to_string(),
+                    };
+                    out.push_str(&format!(
+                        "| `{}` | {} | {} | {} |\n",
+                        param.name,
+                        param.kind,
+                        default,
+                        description.map(markdown_inline).unwrap_or_default()
+                    ));
+                }
+                out.push('\n');
+            } else if !sections.arguments.is_empty() {
+                for argument in &sections.arguments {
+                    out.push_str(&format!("- {}\n", markdown_inline(argument)));
+                }
+                out.push('\n');
+            }
+
+            for example in &sections.examples {
+                out.push_str(&format!("**Example:**\n\n```\n{}\n```\n\n", example));
+            }
+        }
+    }
+
+    format!("{}\n", out.trim_end())
+}
+
+pub fn filters_to_json() -> String {
+    let docs: Vec<serde_json::Value> = grouped()
+        .into_values()
+        .flatten()
+        .map(|doc| {
+            let sections = sections(doc.description);
+            let descriptions = argument_descriptions(&doc, &sections);
+
+            let params: Vec<serde_json::Value> = doc
+                .params
+                .iter()
+                .zip(descriptions)
+                .map(|(param, description)| {
+                    serde_json::json!({
+                        "name": param.name,
+                        "type": param.kind,
+                        "required": param.required,
+                        "default": param.default,
+                        "description": description.map(|d| convert_inline(d, ("`", "`"), ("**", "**"))),
+                    })
+                })
+                .collect();
+
+            serde_json::json!({
+                "name": doc.name,
+                "category": doc.category,
+                "description": sections
+                    .paragraphs
+                    .iter()
+                    .map(|p| convert_inline(p, ("`", "`"), ("**", "**")))
+                    .collect::<Vec<String>>()
+                    .join("\n\n"),
+                "params": params,
+                "examples": sections.examples,
+            })
+        })
+        .collect();
+
+    serde_json::to_string_pretty(&docs).unwrap_or_default()
+}
+
+pub fn filters_to_man() -> String {
+    let mut out = String::from(
+        ".TH MATUGEN-FILTERS 7 \"\" \"matugen\" \"Template filters\"\n\
+         .SH NAME\n\
+         matugen\\-filters \\- filters available in matugen templates\n",
+    );
+
+    for (category, docs) in grouped() {
+        out.push_str(&format!(".SH {}\n", man_escape(&category.to_uppercase())));
+
+        for doc in docs {
+            let sections = sections(doc.description);
+            out.push_str(&format!(".SS {}\n", man_escape(doc.name)));
+
+            for paragraph in &sections.paragraphs {
+                out.push_str(&format!(".PP\n{}\n", man_inline(paragraph)));
+            }
+
+            let descriptions = argument_descriptions(&doc, &sections);
+            for (param, description) in doc.params.iter().zip(descriptions) {
+                let kind = match (param.required, param.default) {
+                    (_, Some(default)) => format!("{}, default {}", param.kind, default),
+                    (true, None) => param.kind.to_string(),
+                    (false, None) => format!("{}, optional", param.kind),
+                };
+                out.push_str(&format!(
+                    ".TP\n.B {}\n({}) {}\n",
+                    man_escape(param.name),
+                    man_escape(&kind),
+                    description.map(man_inline).unwrap_or_default()
+                ));
+            }
+
+            for example in &sections.examples {
+                out.push_str(&format!(
+                    ".PP\nExample:\n.EX\n{}\n.EE\n",
+                    man_escape(example)
+                ));
+            }
+        }
+    }
+
+    out
+}
+
+fn check_example(engine: &mut Engine, example: &str) -> Result<String, String> {
+    if !engine.try_add_template(EXAMPLE_TEMPLATE.to_string(), example.to_string()) {
+        return Err("it could not be parsed".to_string());
+    }
+
+    let res = match engine.render(EXAMPLE_TEMPLATE) {
+        Ok(out) if out.trim().is_empty() => Err("it produced no output".to_string()),
+        Ok(out) => Ok(out),
+        Err(errors) => {
+            for error in &errors {
+                let _ = error.emit(engine);
+            }
+            Err(format!("it produced {} errors", errors.len()))
+        }
+    };
+
+    engine.remove_template(&EXAMPLE_TEMPLATE.to_string());
+    res
+}
+
+/// Renders every documented example through `engine`, returning how many problems were found.
+///
+/// `engine` should only have the built-in filters, the palettes the examples use are added here.
+pub fn check_filter_docs(engine: &mut Engine) -> usize {
+    for (name, colors) in EXAMPLE_PALETTES {
+        let colors: IndexMap<String, Rgb> = colors
+            .iter()
+            .map(|(name, hex)| {
+                let color = Rgb::from_hex_str(hex).expect("example palettes are valid hex");
+                (name.to_string(), color)
+            })
+            .collect();
+        engine.add_palette(name.to_string(), colors);
+    }
+
+    let mut seen = HashSet::new();
+    let mut checked = 0;
+    let mut problems = 0;
+
+    for doc in filter_docs() {
+        if !seen.insert(doc.name) {
+            error!("<yellow>{}</> is documented more than once", doc.name);
+            problems += 1;
+        }
+
+        let examples = sections(doc.description).examples;
+
+        if examples.is_empty() {
+            warn!("<yellow>{}</> has no example", doc.name);
+        }
+
+        for example in examples {
+            checked += 1;
+
+            match check_example(engine, &example) {
+                Ok(out) => debug!("{} => {}", example, out),
+                Err(message) => {
+                    error!(
+                        "The example of <yellow>{}</> failed, {}: {}",
+                        doc.name, message, example
+                    );
+                    problems += 1;
+                }
+            }
+        }
+    }
+
+    info!(
+        "Checked {} examples of {} filters, found {} problems",
+        checked,
+        seen.len(),
+        problems
+    );
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESCRIPTION: &str = r##"<p>Mixes two colors</p>
+
+<p><strong>Arguments:</strong></p>
+
+<ul>
+    <li><code>Color</code> - color to blend with</li>
+    <li><code>Float</code> (optional) - blend amount, default <code>0.5</code></li>
+</ul>
+
+<p><strong>Example:</strong></p>
+<md-card class="code-card">
+    <pre class="code-block"><code class="language-bash">{{ "#000000" | to_color | blend: "#ffffff" }}</code></pre>
+</md-card>
+"##;
+
+    #[test]
+    fn splits_descriptions() {
+        assert_eq!(
+            sections(DESCRIPTION),
+            Sections {
+                paragraphs: vec!["Mixes two colors"],
+                arguments: vec![
+                    "color to blend with",
+                    "blend amount, default <code>0.5</code>"
+                ],
+                examples: vec![r##"{{ "#000000" | to_color | blend: "#ffffff" }}"##.to_string()],
+            }
+        );
+    }
+
+    #[test]
+    fn converts_inline_html() {
+        assert_eq!(
+            markdown_inline("a <code>x | y</code>\n    <strong>b</strong>"),
+            "a `x \\| y` **b**"
+        );
+        assert_eq!(man_inline(".a-<code>b</code>"), "\\&.a\\-\\fBb\\fR");
+    }
+}
@@ -1,6 +1,7 @@
 use colorsys::{Hsl, Rgb};
+use indexmap::IndexMap;
 
-use crate::parser::{Engine, FilterError, SpannedValue, Value};
+use crate::parser::{ColorValue, Engine, FilterError, SpannedValue, Value};
 
 #[derive(Debug)]
 pub enum FilterReturnType {
@@ -8,6 +9,10 @@ pub enum FilterReturnType {
     Rgb(Rgb),
     Hsl(Hsl),
     Bool(bool),
+    Int(i64),
+    Float(f64),
+    Array(Vec<Value>),
+    Map(IndexMap<String, Value>),
 }
 
 pub type FilterFn = fn(
@@ -17,6 +22,18 @@ pub type FilterFn = fn(
     &Engine,
 ) -> Result<FilterReturnType, FilterError>;
 
+/// How filters are stored inside of the [`Engine`], so they can capture state.
+pub type BoxedFilterFn = Box<
+    dyn Fn(
+            &[&str],
+            &[SpannedValue],
+            FilterReturnType,
+            &Engine,
+        ) -> Result<FilterReturnType, FilterError>
+        + Send
+        + Sync,
+>;
+
 impl ToString for FilterReturnType {
     fn to_string(&self) -> String {
         match self {
@@ -27,8 +44,81 @@ impl ToString for FilterReturnType {
                 true => "true".to_owned(),
                 false => "false".to_owned(),
             },
+            FilterReturnType::Int(value) => value.to_string(),
+            FilterReturnType::Float(value) => Value::Float(*value).to_string(),
+            FilterReturnType::Array(values) => values
+                .iter()
+                .map(|v| v.to_string())
+                .collect::<Vec<String>>()
+                .join(", "),
+            FilterReturnType::Map(map) => Value::Map(map.clone()).to_string(),
+        }
+    }
+}
+
+impl FilterReturnType {
+    /// Gets the color out of the value for color filters, keeping whether it is an `Hsl` color.
+    pub fn expect_color(self) -> Result<ColorValue, FilterError> {
+        match self {
+            FilterReturnType::Rgb(color) => Ok(ColorValue::Rgb(color)),
+            FilterReturnType::Hsl(color) => Ok(ColorValue::Hsl(color)),
+            FilterReturnType::String(_) => Err(FilterError::ColorFilterOnString),
+            FilterReturnType::Bool(_) => Err(FilterError::ColorFilterOnBool),
+            FilterReturnType::Int(_) | FilterReturnType::Float(_) => {
+                Err(FilterError::ColorFilterOnNumber)
+            }
+            FilterReturnType::Array(_) => Err(FilterError::FilterOnArray),
+            FilterReturnType::Map(_) => Err(FilterError::FilterOnMap),
+        }
+    }
+
+    /// Like [`expect_color`](Self::expect_color), with `Hsl` colors converted to `Rgb`.
+    pub fn expect_rgb(self) -> Result<Rgb, FilterError> {
+        match self.expect_color()? {
+            ColorValue::Rgb(color) => Ok(color),
+            ColorValue::Hsl(color) => Ok(color.into()),
+        }
+    }
+
+    /// Gets the value as a string for string filters, colors are written in the format of the
+    /// keyword, like `hex` in `colors.primary.default.hex`, or as `hex` without one.
+    pub fn expect_string(self, keywords: &[&str], engine: &Engine) -> Result<String, FilterError> {
+        let format = keywords.last().copied().unwrap_or("hex");
+        let format_color = |color: Rgb| {
+            engine
+                .format_color(color, format)
+                .map(|value| value.to_string())
+                .ok_or_else(|| FilterError::UnknownFormat {
+                    format: format.to_string(),
+                    expected: engine.formats(),
+                })
+        };
+
+        match self {
+            FilterReturnType::String(s) => Ok(s),
+            FilterReturnType::Rgb(color) => format_color(color),
+            FilterReturnType::Hsl(color) => format_color(color.into()),
+            other @ (FilterReturnType::Bool(_)
+            | FilterReturnType::Int(_)
+            | FilterReturnType::Float(_)) => Ok(other.to_string()),
+            FilterReturnType::Array(_) => Err(FilterError::FilterOnArray),
+            FilterReturnType::Map(_) => Err(FilterError::FilterOnMap),
         }
     }
+
+    pub fn variant_name(&self) -> String {
+        match self {
+            FilterReturnType::String(_) => "String",
+            FilterReturnType::Rgb(_) => "Color",
+            FilterReturnType::Hsl(_) => "Hsl Color",
+            FilterReturnType::Bool(_) => "Bool",
+            FilterReturnType::Int(_) => "Int",
+            FilterReturnType::Float(_) => "Float",
+            FilterReturnType::Array(_) => "Array",
+            FilterReturnType::Map(_) => "Map",
+        }
+        .to_string()
+    }
 }
 
 impl From<String> for FilterReturnType {
@@ -45,25 +135,25 @@ impl From<&String> for FilterReturnType {
 
 impl From<i64> for FilterReturnType {
     fn from(value: i64) -> Self {
-        FilterReturnType::String(value.to_string())
+        FilterReturnType::Int(value)
     }
 }
 
 impl From<&i64> for FilterReturnType {
     fn from(value: &i64) -> Self {
-        FilterReturnType::String(value.to_string())
+        FilterReturnType::Int(*value)
     }
 }
 
 impl From<f64> for FilterReturnType {
     fn from(value: f64) -> Self {
-        FilterReturnType::String(value.to_string())
+        FilterReturnType::Float(value)
     }
 }
 
 impl From<&f64> for FilterReturnType {
     fn from(value: &f64) -> Self {
-        FilterReturnType::String(value.to_string())
+        FilterReturnType::Float(*value)
     }
 }
 
@@ -118,9 +208,9 @@ impl From<Value> for FilterReturnType {
             Value::Color(v) => v.into(),
             Value::HslColor(v) => v.into(),
             Value::Bool(boolean) => Self::Bool(boolean),
-            Value::Map(_hash_map) => panic!("Cant convert map to FilterReturnType"),
-            Value::Array(_array) => panic!("Cant convert Array to String"),
-            Value::Null => todo!(),
+            Value::Map(map) => Self::Map(map),
+            Value::Array(array) => Self::Array(array),
+            Value::Null => Self::String(String::new()),
             Value::LazyColor { color, scheme: _ } => FilterReturnType::from(Value::Color(color)),
         }
     }
@@ -135,9 +225,9 @@ impl From<&Value> for FilterReturnType {
             Value::Color(v) => v.into(),
             Value::HslColor(v) => v.into(),
             Value::Bool(v) => v.into(),
-            Value::Map(_hash_map) => panic!("Cant convert map to FilterReturnType"),
-            Value::Array(_array) => panic!("Cant convert Array to String"),
-            Value::Null => todo!(),
+            Value::Map(map) => Self::Map(map.clone()),
+            Value::Array(array) => Self::Array(array.clone()),
+            Value::Null => Self::String(String::new()),
             Value::LazyColor { color, scheme: _ } => color.into(),
         }
     }
@@ -1,6 +1,7 @@
+use chumsky::span::SimpleSpan;
 use colorsys::{Hsl, Rgb};
 
-use crate::parser::Value;
+use crate::parser::{FilterError, SpannedValue, Value};
 
 #[cfg(feature = "filter-docs")]
 #[derive(Debug, Clone)]
@@ -8,6 +9,17 @@ pub struct FilterDoc {
     pub name: &'static str,
     pub category: &'static str,
     pub description: &'static str,
+    pub params: Vec<FilterParamDoc>,
+}
+
+/// The parameters declared in `register_filters!`, with the default as written in the source.
+#[cfg(feature = "filter-docs")]
+#[derive(Debug, Clone)]
+pub struct FilterParamDoc {
+    pub name: &'static str,
+    pub kind: &'static str,
+    pub required: bool,
+    pub default: Option<&'static str>,
 }
 
 #[cfg(feature = "filter-docs")]
@@ -28,7 +40,7 @@ pub fn filter_docs() -> Vec<FilterDoc> {
 #[cfg(feature = "filter-docs")]
 #[macro_export]
 macro_rules! __register_filter_doc {
-    ($name:expr, $category:expr, $doc:expr) => {{
+    ($name:expr, $category:expr, $doc:expr, $params:expr) => {{
         $crate::parser::helpers::FILTER_DOCS
             .get_or_init(|| std::sync::Mutex::new(Vec::new()))
             .lock()
@@ -37,6 +49,7 @@ macro_rules! __register_filter_doc {
                 name: $name,
                 category: $category,
                 description: $doc,
+                params: $params,
             });
     }};
 }
@@ -44,9 +57,32 @@ macro_rules! __register_filter_doc {
 #[cfg(not(feature = "filter-docs"))]
 #[macro_export]
 macro_rules! __register_filter_doc {
-    ($name:expr, $category:expr, $doc:expr) => {};
+    ($name:expr, $category:expr, $doc:expr, $params:expr) => {};
+}
+
+#[macro_export]
+macro_rules! __filter_param_default_doc {
+    () => {
+        None
+    };
+    ($default:expr) => {
+        Some(stringify!($default))
+    };
+}
+
+#[macro_export]
+macro_rules! __filter_param_default {
+    () => {
+        None
+    };
+    ($default:expr) => {
+        Some((|| $crate::parser::Value::from($default)) as fn() -> $crate::parser::Value)
+    };
 }
 
+/// Registers filters along with their docs. Filters can declare their parameters like
+/// `"blend" (color: Rgb, amount: f64 = 0.5) => crate::filters::blend`, which allows
+/// calling them with named arguments and leaving out the ones with defaults.
 #[macro_export]
 macro_rules! register_filters {
     (($engine:expr) {
@@ -54,7 +90,9 @@ macro_rules! register_filters {
             $category:literal => {
                 $(
                     $(#[doc = $doc:literal])*
-                    $name:literal => $func:path
+                    $name:literal $((
+                        $($param:ident: $ty:ty $(= $default:expr)?),* $(,)?
+                    ))? => $func:path
                 ),* $(,)?
             }
         ),* $(,)?
@@ -62,11 +100,30 @@ macro_rules! register_filters {
         $(
             $(
                 $engine.add_filter($name, $func);
+                $(
+                    $engine.set_filter_params($name, vec![$(
+                        $crate::parser::helpers::FilterParam {
+                            name: stringify!($param),
+                            kind: <$ty as $crate::parser::helpers::FilterArg>::TYPE_NAME,
+                            required: <$ty as $crate::parser::helpers::FilterArg>::REQUIRED,
+                            default: $crate::__filter_param_default!($($default)?),
+                            check: <$ty as $crate::parser::helpers::FilterArg>::check,
+                        }
+                    ),*]);
+                )?
 
                 $crate::__register_filter_doc!(
                     $name,
                     $category,
-                    concat!($($doc, "\n"),*)
+                    concat!($($doc, "\n"),*),
+                    vec![$($(
+                        $crate::parser::helpers::FilterParamDoc {
+                            name: stringify!($param),
+                            kind: <$ty as $crate::parser::helpers::FilterArg>::TYPE_NAME,
+                            required: <$ty as $crate::parser::helpers::FilterArg>::REQUIRED,
+                            default: $crate::__filter_param_default_doc!($($default)?),
+                        }
+                    ),*)?]
                 );
             )*
         )*
@@ -205,3 +262,230 @@ impl ExpectFromValue for Hsl {
         }
     }
 }
+
+impl ExpectFromValue for bool {
+    fn expect_from(value: &Value) -> Result<Self, String> {
+        match value {
+            Value::Bool(b) => Ok(*b),
+            other => Err(other.variant_name()),
+        }
+    }
+}
+
+/// A parameter declared in `register_filters!`.
+#[derive(Debug, Clone)]
+pub struct FilterParam {
+    pub name: &'static str,
+    pub kind: &'static str,
+    pub required: bool,
+    pub default: Option<fn() -> Value>,
+    pub check: fn(&Value) -> Result<(), String>,
+}
+
+/// Types that can be used for declared filter parameters, `Option<T>` makes a parameter optional.
+pub trait FilterArg {
+    const TYPE_NAME: &'static str;
+    const REQUIRED: bool = true;
+
+    fn check(value: &Value) -> Result<(), String>;
+}
+
+macro_rules! impl_filter_arg {
+    ($($ty:ty => $name:literal),* $(,)?) => {
+        $(
+            impl FilterArg for $ty {
+                const TYPE_NAME: &'static str = $name;
+
+                fn check(value: &Value) -> Result<(), String> {
+                    <$ty as ExpectFromValue>::expect_from(value).map(|_| ())
+                }
+            }
+        )*
+    };
+}
+
+impl_filter_arg!(
+    String => "String",
+    i64 => "Int",
+    f64 => "Float",
+    bool => "Bool",
+    Rgb => "Color",
+    Hsl => "Hsl Color",
+);
+
+impl<T: FilterArg> FilterArg for Option<T> {
+    const TYPE_NAME: &'static str = T::TYPE_NAME;
+    const REQUIRED: bool = false;
+
+    fn check(value: &Value) -> Result<(), String> {
+        T::check(value)
+    }
+}
+
+/// An argument as written in the template, before it is matched to a parameter.
+#[derive(Debug, Clone)]
+pub struct FilterArgument {
+    pub name: Option<(String, SimpleSpan)>,
+    pub value: SpannedValue,
+}
+
+/// Matches positional and named arguments to the declared parameters, filling in defaults.
+///
+/// The returned arguments are in parameter order, so filters can keep reading them with `expect_args!`.
+/// Optional parameters without a default that are left out become `Null` if a later one is set,
+/// and are dropped otherwise.
+pub fn bind_filter_args(
+    params: &[FilterParam],
+    args: Vec<FilterArgument>,
+    filter_span: SimpleSpan,
+) -> Result<Vec<SpannedValue>, FilterError> {
+    let mut slots: Vec<Option<SpannedValue>> = vec![None; params.len()];
+    let mut positional = 0;
+    let mut seen_named = false;
+
+    for arg in args {
+        let index = match arg.name {
+            None => {
+                if seen_named {
+                    return Err(FilterError::PositionalAfterNamed {
+                        span: arg.value.span,
+                    });
+                }
+                if positional >= params.len() {
+                    return Err(FilterError::TooManyArguments {
+                        expected: params.len(),
+                        span: arg.value.span,
+                    });
+                }
+                positional += 1;
+                positional - 1
+            }
+            Some((name, span)) => {
+                seen_named = true;
+                let Some(index) = params.iter().position(|p| p.name == name) else {
+                    return Err(FilterError::UnknownArgument {
+                        name,
+                        expected: params.iter().map(|p| p.name).collect::<Vec<_>>().join(", "),
+                        span,
+                    });
+                };
+                if slots[index].is_some() {
+                    return Err(FilterError::DuplicateArgument { name, span });
+                }
+                index
+            }
+        };
+
+        (params[index].check)(&arg.value.value).map_err(|actual| {
+            FilterError::InvalidArgumentType {
+                span: arg.value.span,
+                expected: params[index].kind.to_string(),
+                actual,
+            }
+        })?;
+
+        slots[index] = Some(arg.value);
+    }
+
+    let last_set = slots.iter().rposition(|slot| slot.is_some());
+    let mut bound = Vec::with_capacity(params.len());
+
+    for (index, (param, slot)) in params.iter().zip(slots).enumerate() {
+        match (slot, param.default) {
+            (Some(value), _) => bound.push(value),
+            (None, Some(default)) => bound.push(SpannedValue::new(default(), filter_span)),
+            (None, None) if param.required => {
+                return Err(FilterError::MissingArgument {
+                    name: param.name.to_string(),
+                    span: filter_span,
+                })
+            }
+            (None, None) if last_set.is_some_and(|last| last > index) => {
+                bound.push(SpannedValue::new(Value::Null, filter_span))
+            }
+            (None, None) => {}
+        }
+    }
+
+    Ok(bound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> Vec<FilterParam> {
+        vec![
+            FilterParam {
+                name: "color",
+                kind: "Color",
+                required: true,
+                default: None,
+                check: <Rgb as FilterArg>::check,
+            },
+            FilterParam {
+                name: "amount",
+                kind: "Float",
+                required: false,
+                default: Some(|| Value::Float(0.5)),
+                check: <f64 as FilterArg>::check,
+            },
+        ]
+    }
+
+    fn arg(name: Option<&str>, value: Value, start: usize) -> FilterArgument {
+        let span = SimpleSpan::from(start..start + 1);
+        FilterArgument {
+            name: name.map(|name| (name.to_string(), span)),
+            value: SpannedValue::new(value, span),
+        }
+    }
+
+    #[test]
+    fn binds_named_and_default_arguments() {
+        let color = Value::Color(Rgb::from_hex_str("#ff0000").unwrap());
+
+        let bound = bind_filter_args(
+            &params(),
+            vec![arg(Some("color"), color, 5)],
+            SimpleSpan::from(0..1),
+        )
+        .unwrap();
+        assert_eq!(bound.len(), 2);
+        assert!(matches!(bound[1].value, Value::Float(f) if f == 0.5));
+
+        let bound = bind_filter_args(
+            &params(),
+            vec![
+                arg(Some("amount"), Value::Float(0.2), 3),
+                arg(Some("color"), Value::Color(Rgb::default()), 5),
+            ],
+            SimpleSpan::from(0..1),
+        )
+        .unwrap();
+        assert!(matches!(bound[0].value, Value::Color(_)));
+        assert!(matches!(bound[1].value, Value::Float(f) if f == 0.2));
+    }
+
+    #[test]
+    fn reports_the_offending_argument() {
+        let err = bind_filter_args(
+            &params(),
+            vec![arg(None, Value::Int(1), 7)],
+            SimpleSpan::from(0..1),
+        )
+        .unwrap_err();
+        assert!(matches!(err, FilterError::InvalidArgumentType { span, .. } if span.start == 7));
+
+        let err = bind_filter_args(
+            &params(),
+            vec![arg(Some("amout"), Value::Int(1), 9)],
+            SimpleSpan::from(0..1),
+        )
+        .unwrap_err();
+        assert!(matches!(err, FilterError::UnknownArgument { span, .. } if span.start == 9));
+
+        let err = bind_filter_args(&params(), vec![], SimpleSpan::from(0..1)).unwrap_err();
+        assert!(matches!(err, FilterError::MissingArgument { ref name, .. } if name == "color"));
+    }
+}
@@ -1,2 +1,4 @@
+#[cfg(feature = "filter-docs")]
+pub mod docs;
 pub mod filtertype;
 pub mod helpers;
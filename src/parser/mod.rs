@@ -7,7 +7,7 @@ pub mod value;
 pub use engine::Engine;
 pub use filters::{filtertype, helpers};
 
-pub use filtertype::{FilterFn, FilterReturnType};
-pub use value::{SpannedValue, Value};
+pub use filtertype::{BoxedFilterFn, FilterFn, FilterReturnType};
+pub use value::{ColorValue, SpannedValue, Value};
 
 pub use errors::*;
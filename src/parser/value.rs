@@ -7,7 +7,10 @@ use chumsky::span::SimpleSpan;
 use colorsys::{Hsl, Rgb};
 use indexmap::IndexMap;
 
-use crate::parser::{engine::format_color_all, FilterReturnType};
+use crate::{
+    color::format::format_hex_alpha,
+    parser::{engine::format_color_all, FilterReturnType},
+};
 
 #[derive(Debug, Clone)]
 pub enum Value {
@@ -41,6 +44,12 @@ impl SpannedValue {
     pub fn new(value: Value, span: SimpleSpan) -> Self {
         Self { value, span }
     }
+
+    /// A value with a placeholder span, for tests that call filters directly.
+    #[cfg(test)]
+    pub(crate) fn unspanned(value: Value) -> Self {
+        Self::new(value, SimpleSpan::from(0..1))
+    }
 }
 
 impl From<&str> for Value {
@@ -57,7 +66,7 @@ impl From<String> for Value {
 
 impl From<f64> for Value {
     fn from(val: f64) -> Self {
-        Value::Int(val as i64)
+        Value::Float(val)
     }
 }
 
@@ -86,6 +95,10 @@ impl From<FilterReturnType> for Value {
             FilterReturnType::Rgb(rgb) => Value::Color(rgb),
             FilterReturnType::Hsl(hsl) => Value::HslColor(hsl),
             FilterReturnType::Bool(b) => Value::Bool(b),
+            FilterReturnType::Int(i) => Value::Int(i),
+            FilterReturnType::Float(f) => Value::Float(f),
+            FilterReturnType::Array(values) => Value::Array(values),
+            FilterReturnType::Map(map) => Value::Map(map),
         }
     }
 }
@@ -208,3 +221,54 @@ impl From<serde_json::Value> for Value {
         }
     }
 }
+
+/// Colors become `#RRGGBBAA` strings, like they are stored in the context.
+impl From<&Value> for serde_json::Value {
+    fn from(v: &Value) -> Self {
+        match v {
+            Value::Ident(s) => serde_json::Value::String(s.clone()),
+            Value::Int(i) => serde_json::Value::from(*i),
+            Value::Float(f) => serde_json::Number::from_f64(*f)
+                .map(serde_json::Value::Number)
+                .unwrap_or_default(),
+            Value::Bool(b) => serde_json::Value::Bool(*b),
+            Value::Null => serde_json::Value::Null,
+            Value::Color(color) | Value::LazyColor { color, .. } => {
+                serde_json::Value::String(format_hex_alpha(color))
+            }
+            Value::HslColor(color) => serde_json::Value::String(format_hex_alpha(&color.into())),
+            Value::Map(map) => serde_json::Value::Object(
+                map.iter()
+                    .map(|(k, v)| (k.clone(), serde_json::Value::from(v)))
+                    .collect(),
+            ),
+            Value::Array(values) => {
+                serde_json::Value::Array(values.iter().map(serde_json::Value::from).collect())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_floats_as_floats() {
+        assert!(matches!(Value::from(2.0), Value::Float(f) if f == 2.0));
+        assert!(matches!(Value::from(0.5), Value::Float(f) if f == 0.5));
+        assert!(matches!(Value::from(2), Value::Int(2)));
+        assert!(matches!(
+            Value::from(serde_json::json!(2.5)),
+            Value::Float(f) if f == 2.5
+        ));
+        assert!(matches!(Value::from(serde_json::json!(3)), Value::Int(3)));
+    }
+
+    #[test]
+    fn writes_whole_floats_without_decimals() {
+        assert_eq!(Value::Float(2.0).to_string(), "2");
+        assert_eq!(Value::Float(1.2).to_string(), "1.2");
+        assert_eq!(Value::Int(-4).to_string(), "-4");
+    }
+}
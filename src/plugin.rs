@@ -0,0 +1,349 @@
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Write},
+    path::PathBuf,
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::{
+        mpsc::{self, Receiver, RecvTimeoutError},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use color_eyre::{eyre::Context, Report};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    color::parse::parse_css_color,
+    helpers::merge_json,
+    parser::{Engine, FilterError, FilterReturnType, SpannedValue, Value},
+    template::get_absolute_path,
+};
+
+/// How long a plugin has to respond to a request before it is stopped.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a plugin has to exit after its stdin is closed before it is killed.
+const EXIT_TIMEOUT: Duration = Duration::from_millis(500);
+
+/// An executable declared in `[config.plugins]`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PluginConfig {
+    pub command: PathBuf,
+    pub args: Option<Vec<String>>,
+}
+
+/// Messages sent to a plugin, one JSON object per line on its stdin.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum Request<'a> {
+    Init {
+        context: &'a serde_json::Value,
+    },
+    Filter {
+        name: &'a str,
+        value: serde_json::Value,
+        args: Vec<serde_json::Value>,
+        keywords: &'a [&'a str],
+    },
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct InitResponse {
+    #[serde(default)]
+    pub filters: Vec<String>,
+    #[serde(default)]
+    pub context: Option<serde_json::Value>,
+}
+
+#[derive(Deserialize, Debug)]
+struct FilterResponse {
+    value: Option<serde_json::Value>,
+    error: Option<String>,
+}
+
+/// A running plugin. Every request gets exactly one line of JSON back on its stdout, and the
+/// plugin should exit once its stdin is closed.
+pub struct Plugin {
+    name: String,
+    child: Child,
+    stdin: Option<ChildStdin>,
+    /// Lines read from the plugin's stdout by a separate thread, so reads can time out.
+    stdout: Receiver<std::io::Result<String>>,
+    timeout: Duration,
+}
+
+impl Plugin {
+    pub fn spawn(
+        name: &str,
+        config: &PluginConfig,
+        config_path: &Option<PathBuf>,
+    ) -> Result<Plugin, Report> {
+        // Bare names like `python3` are looked up in PATH, paths are relative to the config
+        let command = match config_path {
+            Some(config_path) if config.command.components().count() > 1 => {
+                get_absolute_path(config_path, &config.command)?
+            }
+            _ => config.command.clone(),
+        };
+
+        let mut child = Command::new(&command)
+            .args(config.args.as_deref().unwrap_or_default())
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .wrap_err(format!(
+                "Could not start the '{}' plugin ({})",
+                name,
+                command.display()
+            ))?;
+
+        let stdin = child.stdin.take();
+        let stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for line in stdout.lines() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+
+        Ok(Plugin {
+            name: name.to_string(),
+            child,
+            stdin,
+            stdout: receiver,
+            timeout: RESPONSE_TIMEOUT,
+        })
+    }
+
+    fn request<T: DeserializeOwned>(&mut self, request: &Request) -> Result<T, String> {
+        let stdin = self.stdin.as_mut().ok_or("The plugin has been closed")?;
+
+        let mut message = serde_json::to_string(request).map_err(|e| e.to_string())?;
+        message.push('\n');
+        stdin
+            .write_all(message.as_bytes())
+            .and_then(|_| stdin.flush())
+            .map_err(|e| format!("Could not write to the plugin: {}", e))?;
+
+        let line = match self.stdout.recv_timeout(self.timeout) {
+            Ok(line) => line.map_err(|e| format!("Could not read from the plugin: {}", e))?,
+            Err(RecvTimeoutError::Timeout) => {
+                // A late response would be read as the answer to the next request
+                self.stop();
+                return Err(format!(
+                    "The plugin did not respond within {} seconds",
+                    self.timeout.as_secs_f64()
+                ));
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                return Err("The plugin exited without responding".to_string())
+            }
+        };
+
+        serde_json::from_str(&line)
+            .map_err(|e| format!("Invalid response '{}': {}", line.trim(), e))
+    }
+
+    /// Sends the render data to the plugin, which responds with the filters it provides and
+    /// context to merge into the render data.
+    pub fn init(&mut self, context: &serde_json::Value) -> Result<InitResponse, Report> {
+        self.request(&Request::Init { context })
+            .map_err(|e| Report::msg(format!("The '{}' plugin failed: {}", self.name, e)))
+    }
+
+    pub fn call_filter(
+        &mut self,
+        name: &str,
+        keywords: &[&str],
+        args: &[SpannedValue],
+        original: FilterReturnType,
+    ) -> Result<FilterReturnType, FilterError> {
+        let is_color = matches!(
+            original,
+            FilterReturnType::Rgb(_) | FilterReturnType::Hsl(_)
+        );
+
+        let request = Request::Filter {
+            name,
+            value: serde_json::Value::from(&Value::from(original)),
+            args: args
+                .iter()
+                .map(|a| serde_json::Value::from(&a.value))
+                .collect(),
+            keywords,
+        };
+
+        let plugin = self.name.clone();
+        let error = |message: String| FilterError::PluginError { plugin, message };
+
+        let response: FilterResponse = self.request(&request).map_err(error.clone())?;
+
+        if let Some(message) = response.error {
+            return Err(error(message));
+        }
+
+        Ok(match response.value.unwrap_or_default() {
+            // Colors stay colors, so formats like `colors.primary.default.rgb | filter` work
+            serde_json::Value::String(string) if is_color => match parse_css_color(&string) {
+                Ok(color) => FilterReturnType::Rgb(color),
+                Err(_) => FilterReturnType::String(string),
+            },
+            serde_json::Value::String(string) => FilterReturnType::String(string),
+            other => FilterReturnType::from(Value::from(other)),
+        })
+    }
+
+    /// Closes stdin so the plugin exits, and kills it if it is still running after
+    /// `EXIT_TIMEOUT`.
+    fn stop(&mut self) {
+        drop(self.stdin.take());
+
+        let deadline = Instant::now() + EXIT_TIMEOUT;
+        while Instant::now() < deadline {
+            match self.child.try_wait() {
+                Ok(Some(_)) | Err(_) => return,
+                Ok(None) => thread::sleep(Duration::from_millis(10)),
+            }
+        }
+
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Drop for Plugin {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Starts every plugin, merges the context they provide into `json` and registers their filters.
+pub fn load_plugins(
+    engine: &mut Engine,
+    json: &mut serde_json::Value,
+    plugins: &HashMap<String, PluginConfig>,
+    config_path: &Option<PathBuf>,
+) -> Result<(), Report> {
+    for (name, config) in plugins {
+        let mut plugin = Plugin::spawn(name, config, config_path)?;
+        let response = plugin.init(json)?;
+
+        if let Some(context) = response.context {
+            merge_json(json, context);
+        }
+
+        let plugin = Arc::new(Mutex::new(plugin));
+
+        for filter in response.filters {
+            if engine.has_filter(&filter) {
+                warn!(
+                    "The filter <yellow>{}</> from the <yellow>{}</> plugin has the same name as an existing filter and will be ignored.",
+                    filter, name
+                );
+                continue;
+            }
+
+            let plugin = Arc::clone(&plugin);
+            let filter_name = filter.clone();
+
+            engine.add_filter(
+                filter,
+                move |keywords: &[&str],
+                      args: &[SpannedValue],
+                      original: FilterReturnType,
+                      _engine: &Engine| {
+                    plugin
+                        .lock()
+                        .unwrap_or_else(|e| e.into_inner())
+                        .call_filter(&filter_name, keywords, args, original)
+                },
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::{fs, os::unix::fs::PermissionsExt};
+
+    use super::*;
+
+    const SCRIPT: &str = r##"#!/bin/sh
+read -r init
+echo '{"filters": ["brand"], "context": {"team": {"name": "design"}}}'
+while read -r request; do
+  case "$request" in
+    *'"name":"brand"'*) echo '{"value": "#ff0000"}' ;;
+    *) echo '{"error": "unknown filter"}' ;;
+  esac
+done
+"##;
+
+    #[test]
+    fn talks_to_a_plugin() {
+        let dir = std::env::temp_dir().join(format!("matugen-plugin-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("plugin.sh");
+        fs::write(&path, SCRIPT).unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let config = PluginConfig {
+            command: path,
+            args: None,
+        };
+        let mut plugin = Plugin::spawn("test", &config, &None).unwrap();
+
+        let response = plugin.init(&serde_json::json!({})).unwrap();
+        assert_eq!(response.filters, vec!["brand".to_string()]);
+        assert_eq!(
+            response.context,
+            Some(serde_json::json!({"team": {"name": "design"}}))
+        );
+
+        let color = FilterReturnType::Rgb(colorsys::Rgb::from((0.0, 0.0, 255.0)));
+        match plugin.call_filter("brand", &[], &[], color) {
+            Ok(FilterReturnType::Rgb(color)) => assert_eq!(color.red(), 255.0),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        assert!(matches!(
+            plugin.call_filter("other", &[], &[], FilterReturnType::String(String::new())),
+            Err(FilterError::PluginError { .. })
+        ));
+
+        drop(plugin);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn stops_a_plugin_that_does_not_respond() {
+        let dir = std::env::temp_dir().join(format!("matugen-plugin-hang-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("plugin.sh");
+        fs::write(&path, "#!/bin/sh\nexec sleep 30\n").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let config = PluginConfig {
+            command: path,
+            args: None,
+        };
+        let mut plugin = Plugin::spawn("test", &config, &None).unwrap();
+        plugin.timeout = Duration::from_millis(100);
+
+        let start = Instant::now();
+        assert!(plugin.init(&serde_json::json!({})).is_err());
+        assert!(plugin.init(&serde_json::json!({})).is_err());
+        drop(plugin);
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,426 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::PathBuf,
+    sync::Arc,
+};
+
+use color_eyre::{eyre::Context, Report};
+use colorsys::{ColorAlpha, Hsl, Rgb};
+use material_colors::hct::Hct;
+use rhai::{Array, CallFnOptions, Dynamic, EvalAltResult, FnAccess, Map, Scope, AST};
+
+use crate::{
+    color::{
+        format::{
+            alpha_f64, argb_from_rgb, format_hex, format_hex_alpha, format_hex_stripped,
+            format_hsl, format_hsla, format_hwb, format_lab, format_lch, format_rgb, format_rgba,
+            rgb_from_argb,
+        },
+        parse::parse_css_color,
+    },
+    helpers::merge_json,
+    parser::{Engine, FilterError, FilterReturnType, SpannedValue, Value},
+    template::get_absolute_path,
+};
+
+/// Scripts can define this function to add values to the context, every other public function
+/// is registered as a filter.
+const CONTEXT_FN: &str = "context";
+
+/// A compiled `.rhai` file from `[config.scripts]`.
+pub struct Script {
+    name: String,
+    engine: rhai::Engine,
+    ast: AST,
+    scope: Scope<'static>,
+}
+
+fn to_hct(color: &Rgb) -> Hct {
+    Hct::new(argb_from_rgb(color))
+}
+
+fn from_hct(hct: Hct, alpha: f64) -> Rgb {
+    let mut color = rgb_from_argb(hct.into());
+    color.set_alpha(alpha);
+    color
+}
+
+fn with_hct(color: &Rgb, f: impl FnOnce(&mut Hct)) -> Rgb {
+    let mut hct = to_hct(color);
+    f(&mut hct);
+    from_hct(hct, color.alpha())
+}
+
+/// Exposes colors as a `Color` type with the conversions from `color::format`, plus HCT and HSL
+/// properties.
+fn register_color(engine: &mut rhai::Engine) {
+    engine
+        .register_type_with_name::<Rgb>("Color")
+        .register_fn("color", |string: &str| -> Result<Rgb, Box<EvalAltResult>> {
+            parse_css_color(string).map_err(|e| e.to_string().into())
+        })
+        .register_fn("rgb", |r: f64, g: f64, b: f64| Rgb::new(r, g, b, None))
+        .register_fn("rgba", |r: f64, g: f64, b: f64, a: f64| {
+            Rgb::new(r, g, b, Some(a))
+        })
+        .register_fn("hsl", |h: f64, s: f64, l: f64| {
+            Rgb::from(&Hsl::new(h, s, l, None))
+        })
+        .register_fn("hct", |h: f64, c: f64, t: f64| {
+            from_hct(Hct::from(h, c, t), 1.0)
+        })
+        .register_get_set(
+            "red",
+            |c: &mut Rgb| c.red(),
+            |c: &mut Rgb, v: f64| c.set_red(v),
+        )
+        .register_get_set(
+            "green",
+            |c: &mut Rgb| c.green(),
+            |c: &mut Rgb, v: f64| c.set_green(v),
+        )
+        .register_get_set(
+            "blue",
+            |c: &mut Rgb| c.blue(),
+            |c: &mut Rgb, v: f64| c.set_blue(v),
+        )
+        .register_get_set(
+            "alpha",
+            |c: &mut Rgb| alpha_f64(c.alpha()),
+            |c: &mut Rgb, v: f64| c.set_alpha(v),
+        )
+        .register_get("hue", |c: &mut Rgb| to_hct(c).get_hue())
+        .register_get("chroma", |c: &mut Rgb| to_hct(c).get_chroma())
+        .register_get("tone", |c: &mut Rgb| to_hct(c).get_tone())
+        .register_get("saturation", |c: &mut Rgb| Hsl::from(&*c).saturation())
+        .register_get("lightness", |c: &mut Rgb| Hsl::from(&*c).lightness())
+        .register_fn("with_hue", |c: &mut Rgb, v: f64| {
+            with_hct(c, |hct| hct.set_hue(v))
+        })
+        .register_fn("with_chroma", |c: &mut Rgb, v: f64| {
+            with_hct(c, |hct| hct.set_chroma(v))
+        })
+        .register_fn("with_tone", |c: &mut Rgb, v: f64| {
+            with_hct(c, |hct| hct.set_tone(v))
+        })
+        .register_fn("with_alpha", |c: &mut Rgb, v: f64| {
+            let mut color = c.clone();
+            color.set_alpha(v);
+            color
+        })
+        .register_fn("to_hex", |c: &mut Rgb| format_hex(c))
+        .register_fn("to_hex_stripped", |c: &mut Rgb| format_hex_stripped(c))
+        .register_fn("to_hex_alpha", |c: &mut Rgb| format_hex_alpha(c))
+        .register_fn("to_rgb", |c: &mut Rgb| format_rgb(c))
+        .register_fn("to_rgba", |c: &mut Rgb| format_rgba(c))
+        .register_fn("to_hsl", |c: &mut Rgb| format_hsl(&Hsl::from(&*c)))
+        .register_fn("to_hsla", |c: &mut Rgb| format_hsla(&Hsl::from(&*c)))
+        .register_fn("to_hwb", |c: &mut Rgb| format_hwb(c))
+        .register_fn("to_lab", |c: &mut Rgb| format_lab(c))
+        .register_fn("to_lch", |c: &mut Rgb| format_lch(c))
+        .register_fn("to_string", |c: &mut Rgb| format_hex_alpha(c))
+        .register_fn("to_debug", |c: &mut Rgb| format_hex_alpha(c));
+}
+
+fn to_dynamic(value: Value) -> Dynamic {
+    match value {
+        Value::Ident(string) => string.into(),
+        Value::Int(i) => i.into(),
+        Value::Float(f) => f.into(),
+        Value::Bool(b) => b.into(),
+        Value::Null => Dynamic::UNIT,
+        Value::Color(color) | Value::LazyColor { color, .. } => Dynamic::from(color),
+        Value::HslColor(color) => Dynamic::from(Rgb::from(&color)),
+        Value::Map(map) => Dynamic::from_map(
+            map.into_iter()
+                .map(|(k, v)| (k.into(), to_dynamic(v)))
+                .collect::<Map>(),
+        ),
+        Value::Array(values) => {
+            Dynamic::from_array(values.into_iter().map(to_dynamic).collect::<Array>())
+        }
+    }
+}
+
+fn from_dynamic(value: Dynamic) -> Value {
+    if value.is::<Rgb>() {
+        return Value::Color(value.cast::<Rgb>());
+    }
+
+    if value.is_unit() {
+        Value::Null
+    } else if let Ok(b) = value.as_bool() {
+        Value::Bool(b)
+    } else if let Ok(i) = value.as_int() {
+        Value::Int(i)
+    } else if let Ok(f) = value.as_float() {
+        Value::Float(f)
+    } else if value.is_array() {
+        Value::Array(
+            value
+                .cast::<Array>()
+                .into_iter()
+                .map(from_dynamic)
+                .collect(),
+        )
+    } else if value.is_map() {
+        Value::Map(
+            value
+                .cast::<Map>()
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), from_dynamic(v)))
+                .collect(),
+        )
+    } else {
+        Value::Ident(value.to_string())
+    }
+}
+
+impl Script {
+    pub fn new(name: &str, source: &str) -> Result<Script, Report> {
+        let mut engine = rhai::Engine::new();
+
+        // Scripts only get what matugen gives them, no imports and no runaway loops
+        engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+        engine.set_max_operations(1_000_000);
+        engine.set_max_call_levels(64);
+        engine.on_print(|text| info!("{}", text));
+        engine.on_debug(|text, _, pos| debug!("{} {}", pos, text));
+        register_color(&mut engine);
+
+        let ast = engine
+            .compile(source)
+            .map_err(|e| Report::msg(format!("Could not compile the '{}' script: {}", name, e)))?;
+
+        // Top level variables are evaluated once and visible to every call
+        let mut scope = Scope::new();
+        engine
+            .run_ast_with_scope(&mut scope, &ast)
+            .map_err(|e| Report::msg(format!("The '{}' script failed: {}", name, e)))?;
+
+        Ok(Script {
+            name: name.to_string(),
+            engine,
+            ast,
+            scope,
+        })
+    }
+
+    fn call(&self, function: &str, args: Vec<Dynamic>) -> Result<Dynamic, String> {
+        self.engine
+            .call_fn_with_options(
+                CallFnOptions::new().eval_ast(false),
+                &mut self.scope.clone(),
+                &self.ast,
+                function,
+                args,
+            )
+            .map_err(|e| e.to_string())
+    }
+
+    /// Public functions and the number of arguments they can be called with, not counting the
+    /// value the filter is applied to.
+    fn filters(&self) -> HashMap<String, BTreeSet<usize>> {
+        let mut filters: HashMap<String, BTreeSet<usize>> = HashMap::new();
+
+        for function in self.ast.iter_functions() {
+            if function.access == FnAccess::Private
+                || function.name == CONTEXT_FN
+                || function.params.is_empty()
+            {
+                continue;
+            }
+            filters
+                .entry(function.name.to_string())
+                .or_default()
+                .insert(function.params.len() - 1);
+        }
+
+        filters
+    }
+
+    /// Calls `fn context(ctx)` if the script has it, returning the map it made.
+    pub fn context(&self, json: &serde_json::Value) -> Result<Option<serde_json::Value>, Report> {
+        let has_context = self
+            .ast
+            .iter_functions()
+            .any(|f| f.name == CONTEXT_FN && f.params.len() == 1);
+
+        if !has_context {
+            return Ok(None);
+        }
+
+        let result = self
+            .call(CONTEXT_FN, vec![to_dynamic(Value::from(json.clone()))])
+            .map_err(|e| Report::msg(format!("The '{}' script failed: {}", self.name, e)))?;
+
+        if !result.is_map() {
+            return Err(Report::msg(format!(
+                "The context function of the '{}' script has to return a map, found '{}'",
+                self.name,
+                result.type_name()
+            )));
+        }
+
+        Ok(Some(serde_json::Value::from(&from_dynamic(result))))
+    }
+
+    pub fn call_filter(
+        &self,
+        name: &str,
+        arities: &BTreeSet<usize>,
+        args: &[SpannedValue],
+        original: FilterReturnType,
+    ) -> Result<FilterReturnType, FilterError> {
+        if !arities.contains(&args.len()) {
+            let max = arities.last().copied().unwrap_or_default();
+            return match args.get(max) {
+                Some(arg) if args.len() > max => Err(FilterError::TooManyArguments {
+                    expected: max,
+                    span: arg.span,
+                }),
+                _ => Err(FilterError::NotEnoughArguments),
+            };
+        }
+
+        let mut call_args = vec![to_dynamic(Value::from(original))];
+        call_args.extend(args.iter().map(|arg| to_dynamic(arg.value.clone())));
+
+        let result = self
+            .call(name, call_args)
+            .map_err(|message| FilterError::ScriptError {
+                script: self.name.clone(),
+                message,
+            })?;
+
+        Ok(match from_dynamic(result) {
+            Value::Color(color) => FilterReturnType::Rgb(color),
+            other => FilterReturnType::from(other),
+        })
+    }
+}
+
+/// Registers the filters of a script on the engine, skipping names that are already taken.
+pub fn register_script(engine: &mut Engine, script: Script) {
+    let script = Arc::new(script);
+
+    for (filter, arities) in script.filters() {
+        if engine.has_filter(&filter) {
+            warn!(
+                "The filter <yellow>{}</> from the <yellow>{}</> script has the same name as an existing filter and will be ignored.",
+                filter, script.name
+            );
+            continue;
+        }
+
+        let script = Arc::clone(&script);
+        let filter_name = filter.clone();
+
+        engine.add_filter(
+            filter,
+            move |_keywords: &[&str],
+                  args: &[SpannedValue],
+                  original: FilterReturnType,
+                  _engine: &Engine| {
+                script.call_filter(&filter_name, &arities, args, original)
+            },
+        );
+    }
+}
+
+/// Compiles every script, merges the context they make into `json` and registers their filters.
+pub fn load_scripts(
+    engine: &mut Engine,
+    json: &mut serde_json::Value,
+    paths: &[PathBuf],
+    config_path: &Option<PathBuf>,
+) -> Result<(), Report> {
+    for path in paths {
+        let path = match config_path {
+            Some(config_path) => get_absolute_path(config_path, path)?,
+            None => path.clone(),
+        };
+
+        let source = fs::read_to_string(&path)
+            .wrap_err(format!("Could not read the script {}", path.display()))?;
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let script = Script::new(&name, &source)?;
+
+        if let Some(context) = script.context(json)? {
+            merge_json(json, context);
+        }
+
+        register_script(engine, script);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCRIPT: &str = r#"
+        const STEP = 10.0;
+
+        fn darker(c) { c.with_tone(c.tone - STEP) }
+        fn darker(c, amount) { c.with_tone(c.tone - amount) }
+        fn shout(s) { s.to_upper() + "!" }
+        private fn helper(s) { s }
+
+        fn context(ctx) {
+            #{ brand: #{ mode: ctx.mode, accent: hct(270.0, 40.0, 60.0).to_hex() } }
+        }
+    "#;
+
+    #[test]
+    fn registers_public_functions() {
+        let script = Script::new("test", SCRIPT).unwrap();
+        let filters = script.filters();
+
+        assert_eq!(filters.len(), 2);
+        assert_eq!(filters["darker"], BTreeSet::from([0, 1]));
+        assert_eq!(filters["shout"], BTreeSet::from([0]));
+    }
+
+    #[test]
+    fn calls_filters_and_context() {
+        let script = Script::new("test", SCRIPT).unwrap();
+        let filters = script.filters();
+
+        let color = parse_css_color("#6750A4").unwrap();
+        let tone = to_hct(&color).get_tone();
+
+        match script.call_filter("darker", &filters["darker"], &[], color.into()) {
+            Ok(FilterReturnType::Rgb(darker)) => {
+                assert!((to_hct(&darker).get_tone() - (tone - 10.0)).abs() < 1.0)
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        match script.call_filter(
+            "shout",
+            &filters["shout"],
+            &[],
+            FilterReturnType::String("hi".to_string()),
+        ) {
+            Ok(FilterReturnType::String(s)) => assert_eq!(s, "HI!"),
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let context = script
+            .context(&serde_json::json!({ "mode": "dark" }))
+            .unwrap()
+            .unwrap();
+        assert_eq!(context["brand"]["mode"], "dark");
+        assert!(context["brand"]["accent"]
+            .as_str()
+            .unwrap()
+            .starts_with('#'));
+    }
+}
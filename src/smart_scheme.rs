@@ -1,10 +1,11 @@
-use std::path::Path;
-
 use color_eyre::Report;
-use image::{imageops::FilterType, DynamicImage, GenericImageView, ImageReader};
+use image::{imageops::FilterType, GenericImageView};
 use material_colors::hct::Hct;
 
-use crate::color::format::argb_from_rgb;
+use crate::color::{
+    format::argb_from_rgb,
+    region::{Region, WeightedImage},
+};
 use crate::scheme::{SchemeTypes, SchemesEnum};
 use colorsys::Rgb;
 
@@ -13,39 +14,41 @@ pub struct SmartOpts {
     pub variant: SchemeTypes,
 }
 
-fn calc_colorfulness(image: &DynamicImage) -> f64 {
-    let rgb_image = image.to_rgb8();
-    let pixels = rgb_image.pixels();
-
+fn calc_colorfulness(frames: &[WeightedImage]) -> f64 {
     let mut rg_sum = 0.0;
     let mut yb_sum = 0.0;
     let mut rg_sq_sum = 0.0;
     let mut yb_sq_sum = 0.0;
-    let mut count = 0u64;
+    let mut count = 0.0;
+
+    for image in frames {
+        let rgb_image = image.image.to_rgb8();
 
-    for pixel in pixels {
-        let r = pixel[0] as f64;
-        let g = pixel[1] as f64;
-        let b = pixel[2] as f64;
+        for (x, y, pixel) in rgb_image.enumerate_pixels() {
+            let weight = image.weight(x, y);
+            let r = pixel[0] as f64;
+            let g = pixel[1] as f64;
+            let b = pixel[2] as f64;
 
-        let rg = (r - g).abs();
-        let yb = (0.5 * (r + g) - b).abs();
+            let rg = (r - g).abs();
+            let yb = (0.5 * (r + g) - b).abs();
 
-        rg_sum += rg;
-        yb_sum += yb;
-        rg_sq_sum += rg * rg;
-        yb_sq_sum += yb * yb;
-        count += 1;
+            rg_sum += rg * weight;
+            yb_sum += yb * weight;
+            rg_sq_sum += rg * rg * weight;
+            yb_sq_sum += yb * yb * weight;
+            count += weight;
+        }
     }
 
-    if count == 0 {
+    if count == 0.0 {
         return 0.0;
     }
 
-    let mean_rg = rg_sum / count as f64;
-    let mean_yb = yb_sum / count as f64;
-    let variance_rg = (rg_sq_sum / count as f64) - (mean_rg * mean_rg);
-    let variance_yb = (yb_sq_sum / count as f64) - (mean_yb * mean_yb);
+    let mean_rg = rg_sum / count;
+    let mean_yb = yb_sum / count;
+    let variance_rg = (rg_sq_sum / count) - (mean_rg * mean_rg);
+    let variance_yb = (yb_sq_sum / count) - (mean_yb * mean_yb);
     let std_rg = variance_rg.sqrt().max(0.0);
     let std_yb = variance_yb.sqrt().max(0.0);
 
@@ -61,11 +64,53 @@ fn detect_variant(colorfulness: f64) -> SchemeTypes {
     }
 }
 
-fn detect_mode(image: &DynamicImage) -> SchemesEnum {
-    let resized = image.resize_exact(1, 1, FilterType::Lanczos3);
-    let pixel = resized.get_pixel(0, 0);
+/// The weighted average color of the image.
+fn weighted_average(image: &WeightedImage) -> Rgb {
+    let mut sum = [0.0; 3];
+    let mut total = 0.0;
+
+    for (x, y, pixel) in image.image.to_rgb8().enumerate_pixels() {
+        let weight = image.weight(x, y);
+        for (channel, value) in sum.iter_mut().zip(pixel.0) {
+            *channel += value as f64 * weight;
+        }
+        total += weight;
+    }
+
+    if total == 0.0 {
+        return Rgb::default();
+    }
+
+    Rgb::from((sum[0] / total, sum[1] / total, sum[2] / total))
+}
+
+fn average_color(image: &WeightedImage) -> Rgb {
+    if image.is_weighted() {
+        weighted_average(image)
+    } else {
+        let resized = image.image.resize_exact(1, 1, FilterType::Lanczos3);
+        let pixel = resized.get_pixel(0, 0);
+        Rgb::from((pixel[0] as f64, pixel[1] as f64, pixel[2] as f64))
+    }
+}
+
+fn detect_mode(frames: &[WeightedImage]) -> SchemesEnum {
+    let mut sum = [0.0; 3];
+    let mut total = 0.0;
+    for frame in frames {
+        let rgb = average_color(frame);
+        sum[0] += rgb.red() * frame.scale();
+        sum[1] += rgb.green() * frame.scale();
+        sum[2] += rgb.blue() * frame.scale();
+        total += frame.scale();
+    }
+
+    if total == 0.0 {
+        return SchemesEnum::Dark;
+    }
+
+    let rgb = Rgb::from((sum[0] / total, sum[1] / total, sum[2] / total));
 
-    let rgb = Rgb::from((pixel[0] as f64, pixel[1] as f64, pixel[2] as f64));
     let argb = argb_from_rgb(&rgb);
     let hct: Hct = argb.into();
 
@@ -76,12 +121,15 @@ fn detect_mode(image: &DynamicImage) -> SchemesEnum {
     }
 }
 
-pub fn get_smart_opts(image_path: &Path) -> Result<SmartOpts, Report> {
-    let img = ImageReader::open(image_path)?.decode()?;
-    let thumb = img.thumbnail(128, 128);
+pub fn get_smart_opts(images: &[(&str, f64)], region: &Region) -> Result<SmartOpts, Report> {
+    let thumbs: Vec<WeightedImage> = region
+        .open_all(images)?
+        .iter()
+        .map(|frame| frame.thumbnail(128, 128))
+        .collect();
 
-    let mode = detect_mode(&thumb);
-    let colorfulness = calc_colorfulness(&thumb);
+    let mode = detect_mode(&thumbs);
+    let colorfulness = calc_colorfulness(&thumbs);
     let variant = detect_variant(colorfulness);
 
     Ok(SmartOpts { mode, variant })
@@ -90,6 +138,7 @@ pub fn get_smart_opts(image_path: &Path) -> Result<SmartOpts, Report> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use image::DynamicImage;
 
     #[test]
     fn test_colorfulness_grayscale() {
@@ -98,7 +147,7 @@ mod tests {
             128,
             image::Rgb([128, 128, 128]),
         ));
-        let score = calc_colorfulness(&img);
+        let score = calc_colorfulness(&[img.into()]);
         assert!(
             score < 1.0,
             "Grayscale image should have near-zero colorfulness, got {score}"
@@ -117,7 +166,7 @@ mod tests {
             }
         }
         let img = DynamicImage::ImageRgb8(img_buf);
-        let score = calc_colorfulness(&img);
+        let score = calc_colorfulness(&[img.into()]);
         assert!(
             score > 20.0,
             "Colorful gradient should score high, got {score}"
@@ -140,7 +189,7 @@ mod tests {
             128,
             image::Rgb([20, 20, 30]),
         ));
-        assert!(matches!(detect_mode(&img), SchemesEnum::Dark));
+        assert!(matches!(detect_mode(&[img.into()]), SchemesEnum::Dark));
     }
 
     #[test]
@@ -150,6 +199,6 @@ mod tests {
             128,
             image::Rgb([240, 240, 250]),
         ));
-        assert!(matches!(detect_mode(&img), SchemesEnum::Light));
+        assert!(matches!(detect_mode(&[img.into()]), SchemesEnum::Light));
     }
 }
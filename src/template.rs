@@ -7,7 +7,7 @@ use material_colors::theme::Theme;
 use serde_json::json;
 
 use crate::{
-    color::color::get_closest_color,
+    color::color::{get_closest_color, palette_from_definitions, ColorsToCompare},
     helpers::{
         apply_opacity_to_schemes, generate_schemes_and_theme, get_syntax, merge_json_source,
     },
@@ -34,7 +34,7 @@ pub struct Template {
     pub input_path: PathBuf,
     pub output_path: Option<OutputPath>,
     pub mode: Option<SchemesEnum>,
-    pub colors_to_compare: Option<Vec<crate::color::color::ColorDefinition>>,
+    pub colors_to_compare: Option<ColorsToCompare>,
     pub compare_to: Option<String>,
     pub pre_hook: Option<String>,
     pub post_hook: Option<String>,
@@ -179,6 +179,7 @@ impl TemplateFile<'_> {
                 } else {
                     let (mut schemes, _, theme, mut base16) = generate_schemes_and_theme(
                         &self.state.args,
+                        &self.state.source,
                         &self.state.config_file,
                         scheme_type,
                     )?;
@@ -365,7 +366,7 @@ fn change_scheme_type(
 pub fn format_hook(
     engine: &mut Engine,
     hook: &String,
-    colors_to_compare: &Option<Vec<crate::color::color::ColorDefinition>>,
+    colors_to_compare: &Option<ColorsToCompare>,
     compare_to: &Option<String>,
 ) -> Result<(), Report> {
     if let (Some(compare), Some(to)) = (colors_to_compare, compare_to) {
@@ -379,7 +380,18 @@ pub fn format_hook(
                 std::process::exit(1);
             }
         };
-        let closest_color = get_closest_color(compare, &res)?;
+        let closest_color = match compare {
+            ColorsToCompare::Colors(colors) => {
+                get_closest_color(&palette_from_definitions(colors)?, &res)?
+            }
+            ColorsToCompare::Palette(name) => {
+                let palette = engine.get_palette(name).ok_or(Report::msg(format!(
+                    "Could not find the palette: {}, palettes can be defined in [config.palettes]",
+                    name
+                )))?;
+                get_closest_color(palette, &res)?
+            }
+        };
         engine.add_context(json!({
             "closest_color": closest_color
         }));
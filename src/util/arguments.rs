@@ -2,7 +2,13 @@ use clap::{ArgAction, Parser};
 use serde::{Deserialize, Serialize};
 use std::{fmt::Debug, ops::RangeBounds, path::PathBuf};
 
-use crate::{color::base16::Backend, SchemesEnum};
+use crate::{
+    color::{
+        base16::Backend,
+        region::{Crop, Length},
+    },
+    SchemesEnum,
+};
 
 #[derive(Parser, Clone)]
 #[command(version, long_about = None)]
@@ -10,7 +16,7 @@ pub struct Cli {
     /// Optional name to operate on
     // name: Option<String>,
     #[command(subcommand)]
-    pub source: crate::color::color::Source,
+    pub source: crate::color::color::SourceArg,
 
     /// Sets a custom color scheme type
     #[arg(
@@ -110,6 +116,16 @@ pub struct Cli {
     #[arg(long, global = true, action=ArgAction::SetTrue)]
     pub filter_docs_html: Option<bool>,
 
+    #[cfg(feature = "filter-docs")]
+    /// Outputs filter documentation in the given format
+    #[arg(value_enum, long, global = true)]
+    pub filter_docs: Option<FilterDocsFormat>,
+
+    #[cfg(feature = "filter-docs")]
+    /// Renders every example in the filter documentation to check that it works
+    #[arg(long, global = true, action=ArgAction::SetTrue)]
+    pub check_filter_docs: Option<bool>,
+
     /// Value from -∞ to 1. -∞ represents minimum lightness, 0 represents
     /// standard (i.e. the design as spec'd), and 1 represents maximum lightness.
     /// For dark schemes, if the considered lightnesses are between 0 and 1 then this applies an affine
@@ -134,10 +150,49 @@ pub struct Cli {
     #[arg(long, global = true, value_parser = clap::builder::RangedI64ValueParser::<i64>::new().range(0..4))]
     pub source_color_index: Option<i64>,
 
+    /// Algorithm used to reduce the image to the colors that are scored for the source color
+    #[arg(value_enum, long, global = true)]
+    pub quantizer: Option<QuantizerType>,
+
+    /// Only extracts colors from this rectangle of the image, written as X,Y,WIDTH,HEIGHT in
+    /// pixels or percentages, for example `0,0,50%,100%`
+    #[arg(long, global = true, value_name = "X,Y,WIDTH,HEIGHT")]
+    pub crop: Option<Crop>,
+
+    /// Ignores a border of this many pixels, or percent of the size, on every side of the image
+    #[arg(long, global = true, value_name = "LENGTH")]
+    pub margin: Option<Length>,
+
+    /// How much more the centre of the image counts than the corners when extracting colors,
+    /// 0 counts every pixel the same
+    #[arg(long, global = true)]
+    pub center_weight: Option<f64>,
+
+    /// Grayscale image stretched over the source image, black areas are ignored and white
+    /// areas count fully when extracting colors
+    #[arg(long, global = true, value_name = "FILE")]
+    pub mask: Option<PathBuf>,
+
+    /// How transparent pixels are handled when extracting colors from an image
+    #[arg(value_enum, long, global = true)]
+    pub alpha_mode: Option<AlphaMode>,
+
+    /// Samples animated GIF, APNG and WebP images at least N frames apart, pooling up to 32 frames
+    #[arg(long, global = true, value_name = "N")]
+    pub frame_step: Option<usize>,
+
+    /// The color semi-transparent pixels are composited over with `--alpha-mode composite`
+    #[arg(long, global = true, value_name = "STRING")]
+    pub alpha_background: Option<String>,
+
     /// Will print the possible source colors for an image instead of setting a theme
     #[arg(long, global = true, action=ArgAction::SetTrue)]
     pub show_source_colors: Option<bool>,
 
+    /// Prints a report of how distinguishable important color pairs are with color vision deficiencies
+    #[arg(long, global = true, action=ArgAction::SetTrue)]
+    pub check_cvd: Option<bool>,
+
     // Value from 0.0 to 1.0 (inclusive)
     // This will set the opacity for all colors inside templates.
     #[arg(long, global = true , value_parser = |s: &str| validate_float_range(s, 0.0..=1.0))]
@@ -187,6 +242,15 @@ pub enum ColorFormat {
     Hsl { string: String },
 }
 
+#[cfg(feature = "filter-docs")]
+#[derive(Debug, Clone, clap::ValueEnum)]
+pub enum FilterDocsFormat {
+    Html,
+    Markdown,
+    Json,
+    Man,
+}
+
 #[derive(Debug, Clone, clap::ValueEnum)]
 pub enum Format {
     Hex,
@@ -195,6 +259,16 @@ pub enum Format {
     Hsl,
     Hsla,
     Strip,
+    ArgbHex,
+    ArgbInt,
+    RgbFloat,
+    RgbaFloat,
+    RgbPercent,
+    Hwb,
+    Lab,
+    Lch,
+    Ansi256,
+    Xterm,
 }
 
 impl ToString for Format {
@@ -206,11 +280,57 @@ impl ToString for Format {
             Format::Hsl => "hsl",
             Format::Hsla => "hsla",
             Format::Strip => "hex_stripped",
+            Format::ArgbHex => "argb_hex",
+            Format::ArgbInt => "argb_int",
+            Format::RgbFloat => "rgb_float",
+            Format::RgbaFloat => "rgba_float",
+            Format::RgbPercent => "rgb_percent",
+            Format::Hwb => "hwb",
+            Format::Lab => "lab",
+            Format::Lch => "lch",
+            Format::Ansi256 => "ansi256",
+            Format::Xterm => "xterm",
         }
         .to_owned()
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuantizerType {
+    /// Wu followed by Wsmeans, as used by Material You
+    #[default]
+    Celebi,
+    Wu,
+    Wsmeans,
+    Kmeans,
+    MedianCut,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AlphaMode {
+    /// Only uses fully opaque pixels
+    #[default]
+    Opaque,
+    /// Composites every pixel over the alpha background
+    Composite,
+    /// Uses every pixel that isn't fully transparent, ignoring its opacity
+    SkipTransparent,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, clap::ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PickMode {
+    /// A random image, other than the one picked last time
+    #[default]
+    Random,
+    /// The image after the one picked last time, by file name
+    Next,
+    /// Spreads the images over the day, picking the one for the current local time
+    TimeOfDay,
+}
+
 #[derive(Debug, Clone, clap::ValueEnum, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub enum SelectionPreference {
@@ -1,6 +1,6 @@
 #[cfg(feature = "dump-json")]
 use indexmap::IndexMap;
-use material_colors::color::Argb;
+use material_colors::color::{Argb, Lab};
 use owo_colors::Style;
 
 use colorsys::Rgb;
@@ -11,7 +11,12 @@ use material_colors::{palette::TonalPalette, theme::Palettes};
 use serde_json::{Map, Value};
 
 use crate::{
-    color::{format::format_hex_alpha, parse::parse_css_color},
+    color::{
+        cvd::{simulate, Deficiency},
+        format::{argb_from_rgb, format_hex_alpha},
+        math::get_color_distance_lab,
+        parse::parse_css_color,
+    },
     parser::engine::format_color,
     scheme::SchemesEnum,
     Schemes,
@@ -22,6 +27,23 @@ use super::arguments::Format;
 
 use crate::color::format::rgb_from_argb;
 
+// Pairs of colors that should stay distinguishable from each other
+const CVD_PAIRS: &[(&str, &str)] = &[
+    ("primary", "on_primary"),
+    ("secondary", "on_secondary"),
+    ("tertiary", "on_tertiary"),
+    ("error", "on_error"),
+    ("surface", "on_surface"),
+    ("primary_container", "on_primary_container"),
+    ("error_container", "on_error_container"),
+    ("error", "primary"),
+    ("error", "surface"),
+    ("primary", "tertiary"),
+];
+
+// Lab distance below which two colors are considered hard to tell apart
+const CVD_MIN_DISTANCE: f64 = 20.0;
+
 const DEFAULT_TONES: [i32; 18] = [
     0, 5, 10, 15, 20, 25, 30, 35, 40, 50, 60, 70, 80, 90, 95, 98, 99, 100,
 ];
@@ -64,6 +86,71 @@ pub fn show_color(
     println!["{table}"];
 }
 
+pub fn show_cvd_report(schemes: &Schemes) {
+    let mut table = Table::new();
+    table
+        .load_preset(UTF8_FULL_CONDENSED)
+        .apply_modifier(UTF8_ROUND_CORNERS)
+        .set_style(comfy_table::TableComponent::VerticalLines, '│');
+
+    let mut header = vec![
+        Cell::new("PAIR").set_alignment(CellAlignment::Center),
+        Cell::new("SCHEME").set_alignment(CellAlignment::Center),
+        Cell::new("NORMAL").set_alignment(CellAlignment::Center),
+    ];
+    header.extend(
+        Deficiency::ALL
+            .iter()
+            .map(|d| Cell::new(d.name().to_uppercase()).set_alignment(CellAlignment::Center)),
+    );
+    table.set_header(header);
+
+    let mut flagged = 0;
+
+    for (scheme_name, scheme) in [("light", &schemes.light), ("dark", &schemes.dark)] {
+        for (first, second) in CVD_PAIRS {
+            let (Some(a), Some(b)) = (scheme.get(*first), scheme.get(*second)) else {
+                continue;
+            };
+            let (a, b) = (rgb_from_argb(*a), rgb_from_argb(*b));
+
+            let mut distances = vec![get_color_distance_lab(
+                &Lab::from(argb_from_rgb(&a)),
+                &Lab::from(argb_from_rgb(&b)),
+            )];
+            distances.extend(Deficiency::ALL.iter().map(|d| {
+                get_color_distance_lab(
+                    &Lab::from(argb_from_rgb(&simulate(&a, *d))),
+                    &Lab::from(argb_from_rgb(&simulate(&b, *d))),
+                )
+            }));
+
+            let mut row = vec![
+                Cell::new(format!("{first} / {second}")),
+                Cell::new(scheme_name),
+            ];
+            // The first distance is without simulation, only the simulated ones flag a pair
+            if distances[1..].iter().any(|d| *d < CVD_MIN_DISTANCE) {
+                flagged += 1;
+            }
+            for distance in distances {
+                let cell = Cell::new(format!("{distance:.1}")).set_alignment(CellAlignment::Right);
+                if distance < CVD_MIN_DISTANCE {
+                    row.push(cell.fg(comfy_table::Color::Red));
+                } else {
+                    row.push(cell);
+                }
+            }
+            table.add_row(row);
+        }
+    }
+
+    println!["{table}"];
+    println!(
+        "{flagged} pair(s) have a Lab distance below {CVD_MIN_DISTANCE} under at least one deficiency and may be hard to distinguish"
+    );
+}
+
 pub fn transform_colors(value: &mut Value, format: &str, keep_color_key: bool) {
     match value {
         Value::Object(map) => {
@@ -10,7 +10,12 @@ use color_eyre::{Help, Report};
 use serde::{Deserialize, Serialize};
 
 use super::arguments::Cli;
-use crate::{util::arguments::SelectionPreference, wallpaper::Wallpaper, Template};
+use crate::{
+    color::region::{Crop, Length},
+    util::arguments::{AlphaMode, QuantizerType, SelectionPreference},
+    wallpaper::Wallpaper,
+    Template,
+};
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Config {
@@ -29,6 +34,21 @@ pub struct Config {
     pub prefer: Option<SelectionPreference>,
     pub contrast: Option<f64>,
     pub source_color_index: Option<i64>,
+    pub quantizer: Option<QuantizerType>,
+    pub max_colors: Option<usize>,
+    pub chroma_threshold: Option<f64>,
+    pub crop: Option<Crop>,
+    pub margin: Option<Length>,
+    pub center_weight: Option<f64>,
+    pub mask: Option<PathBuf>,
+    pub alpha_mode: Option<AlphaMode>,
+    pub alpha_background: Option<String>,
+    pub frame_step: Option<usize>,
+    pub palettes: Option<HashMap<String, Vec<crate::color::color::ColorDefinition>>>,
+    pub formats: Option<HashMap<String, String>>,
+    pub filters: Option<HashMap<String, String>>,
+    pub plugins: Option<HashMap<String, crate::plugin::PluginConfig>>,
+    pub scripts: Option<Vec<PathBuf>>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]